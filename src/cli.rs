@@ -1,4 +1,9 @@
+use crate::commands::export::ExportFormat;
+use crate::state::ProcessStatus;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "wortex")]
@@ -36,21 +41,35 @@ pub enum Commands {
         #[arg(long, group = "cmd_type")]
         cmd: Option<String>,
 
-        /// Agent identifier passed to claude
+        /// Agent identifier passed to claude [config: new.agent]
         #[arg(long)]
         agent: Option<String>,
 
-        /// Kill pane on exit. No value = exit 0. "any" = any code. "0,1" = specific codes
+        /// Kill pane on exit. No value = exit 0. "any" = any code. "0,1" = specific codes [config: new.exit_kill]
         #[arg(long, value_name = "CODES")]
         exit_kill: Option<Option<String>>,
 
-        /// Git remote
-        #[arg(long, default_value = "origin")]
-        remote: String,
+        /// Git remote [config: new.remote, default: origin]
+        #[arg(long)]
+        remote: Option<String>,
+
+        /// Base branch to create worktree from [config: new.base, default: main]
+        #[arg(long)]
+        base: Option<String>,
 
-        /// Base branch to create worktree from
-        #[arg(long, default_value = "main")]
-        base: String,
+        /// Use an existing branch: check out the local branch, or track <remote>/<branch>
+        #[arg(long)]
+        existing: bool,
+    },
+
+    /// Create several sessions from a YAML or JSON manifest
+    Batch {
+        /// Manifest file (tasks.yaml or tasks.json)
+        manifest: PathBuf,
+
+        /// Git remote for all tasks [config: new.remote, default: origin]
+        #[arg(long)]
+        remote: Option<String>,
     },
 
     /// Internal command executed inside tmux window
@@ -63,25 +82,177 @@ pub enum Commands {
 
     /// List tracked worktrees
     List {
-        /// Output as JSON
-        #[arg(long)]
+        /// Output as JSON [config: list.json]
+        #[arg(long, overrides_with = "no_json")]
         json: bool,
+
+        /// Print the table even if list.json is set
+        #[arg(long, overrides_with = "json")]
+        no_json: bool,
     },
 
     /// Switch to a worktree's tmux window
     Switch {
-        /// Branch name
+        /// Branch name, or project:branch to target another repo
         branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
     },
 
     /// Kill a worktree and its tmux window
     Kill {
-        /// Branch name
+        /// Branch name, or project:branch to target another repo
         branch: String,
 
-        /// Keep the worktree directory
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Keep the worktree directory [config: kill.keep_worktree]
+        #[arg(long, overrides_with = "no_keep_worktree")]
+        keep_worktree: bool,
+
+        /// Remove the worktree even if kill.keep_worktree is set
+        #[arg(long, overrides_with = "keep_worktree")]
+        no_keep_worktree: bool,
+    },
+
+    /// Merge a session's branch into its base branch in the main repo
+    Merge {
+        /// Branch name, or project:branch to target another repo
+        branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Squash the branch into a single commit
+        #[arg(long, group = "strategy")]
+        squash: bool,
+
+        /// Rebase the branch onto the base in its worktree, then fast-forward
+        #[arg(long, group = "strategy")]
+        rebase: bool,
+
+        /// Only fast-forward; refuse if the base has moved on
+        #[arg(long, group = "strategy")]
+        ff_only: bool,
+
+        /// Branch to merge into; must be checked out in the main repo [default: the session's base]
+        #[arg(long, value_name = "BASE")]
+        into: Option<String>,
+
+        /// After merging, kill the window, worktree and branch as `wortex kill` does
         #[arg(long)]
+        kill: bool,
+
+        /// With --kill, keep the worktree directory [config: kill.keep_worktree]
+        #[arg(long, requires = "kill", overrides_with = "no_keep_worktree")]
         keep_worktree: bool,
+
+        /// With --kill, remove the worktree even if kill.keep_worktree is set
+        #[arg(long, requires = "kill", overrides_with = "keep_worktree")]
+        no_keep_worktree: bool,
+    },
+
+    /// Push a session's branch to its remote and set it as upstream
+    Push {
+        /// Branch name, or project:branch to target another repo
+        branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Overwrite the remote branch if it is where the last fetch saw it
+        #[arg(long)]
+        force_with_lease: bool,
+    },
+
+    /// Show what a session changed since it branched from <remote>/<base>
+    Diff {
+        /// Branch name, or project:branch to target another repo
+        branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Show a diffstat
+        #[arg(long, group = "format")]
+        stat: bool,
+
+        /// Show only the names of changed files
+        #[arg(long, group = "format")]
+        name_only: bool,
+
+        /// Show the full patch (default)
+        #[arg(long, group = "format")]
+        patch: bool,
+
+        /// Per-file additions and deletions as JSON
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Type text into a session's tmux pane
+    Send {
+        /// Branch name, or project:branch to target another repo
+        branch: String,
+
+        /// Text to send; multi-line text is sent as a bracketed paste
+        text: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Press Enter after the text
+        #[arg(long)]
+        enter: bool,
+    },
+
+    /// Wait for sessions to finish and exit with their exit code
+    Wait {
+        /// Branch names, or project:branch to target another repo
+        #[arg(required = true)]
+        branches: Vec<String>,
+
+        /// Project to look the branches up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Return as soon as one session finishes, with its exit code
+        #[arg(long, conflicts_with = "all")]
+        any: bool,
+
+        /// Wait for every session (default); exit 0 only if all succeeded
+        #[arg(long)]
+        all: bool,
+
+        /// Give up after this long, e.g. 90, 30s, 10m, 2h (exit code 124)
+        #[arg(long, value_parser = parse_duration)]
+        timeout: Option<Duration>,
+
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Run the session's command again in its existing worktree
+    Restart {
+        /// Branch name, or project:branch to target another repo
+        branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Restart even if the command is still running (kills it)
+        #[arg(long)]
+        force: bool,
     },
 
     /// Clean up stale entries
@@ -90,10 +261,41 @@ pub enum Commands {
         /// Show what would be removed without removing
         #[arg(long)]
         dry_run: bool,
+
+        /// Also delete archived (killed or cleaned up) entries and their logs
+        #[arg(long)]
+        purge: bool,
     },
 
     /// Show git status for all tracked worktrees
-    Status,
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the files a session read and modified, from its tool calls
+    Files {
+        /// Branch name or project:branch (killed entries included)
+        #[arg(required_unless_present = "overlap")]
+        branch: Option<String>,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// List files modified by two or more live sessions of the same repo instead
+        #[arg(long, conflicts_with_all = ["branch", "project"])]
+        overlap: bool,
+
+        /// With --overlap, only count edits made since a time, given as for `tools --since`
+        #[arg(long, requires = "overlap", conflicts_with = "branch", value_parser = parse_time)]
+        since: Option<DateTime<Utc>>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Internal command to log tool usage from Claude hooks
     #[command(hide = true)]
@@ -102,29 +304,163 @@ pub enum Commands {
         /// Session ID (entry UUID)
         session_id: String,
 
-        /// Hook type: "pre" or "post"
+        /// Hook type: "pre", "post" or "notification"
         hook_type: String,
     },
 
     /// Query logged tool calls for a session
+    #[command(args_conflicts_with_subcommands = true)]
     Tools {
-        /// Branch name (optional, shows all if not specified)
+        #[command(subcommand)]
+        action: Option<ToolsCommands>,
+
+        /// Branch name or project:branch (optional, shows all if not specified)
         branch: Option<String>,
 
+        /// Project to look the branch up in (default: the current repo); without a branch, show only this project's calls
+        #[arg(long)]
+        project: Option<String>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
 
-        /// Show only specific hook type: "pre" or "post"
+        /// Show only calls in a given phase: "pre" (no result yet) or "post" (completed)
         #[arg(long)]
         hook_type: Option<String>,
 
-        /// Limit number of results
-        #[arg(long, short)]
+        /// Show only the most recent N calls
+        #[arg(long, short = 'n', short_alias = 'l')]
         limit: Option<usize>,
+
+        /// Show only calls to this tool (repeatable)
+        #[arg(long = "tool", value_name = "NAME")]
+        tools: Vec<String>,
+
+        /// Show only calls made since a time: a duration ago (30m, 2h, 1d) or a date (2025-01-31, 2025-01-31 14:00, RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        since: Option<DateTime<Utc>>,
+
+        /// Show only calls made until a time, given as for --since
+        #[arg(long, value_parser = parse_time)]
+        until: Option<DateTime<Utc>>,
+
+        /// Show only calls of sessions with this status (spawned, running, waiting, exited, killed, signaled, external)
+        #[arg(long, value_parser = parse_status)]
+        status: Option<ProcessStatus>,
+
+        /// Show only calls of sessions that exited with this code
+        #[arg(long)]
+        exit_code: Option<i32>,
+
+        /// Show only calls with a string value in the input containing this text
+        #[arg(long)]
+        grep: Option<String>,
+
+        /// Show only calls with a string value in the input matching this regular expression
+        #[arg(long, value_parser = parse_regex)]
+        regex: Option<String>,
+
+        /// Show per-tool counts, files touched, top Bash commands and activity instead of calls
+        #[arg(long, conflicts_with_all = ["hook_type", "limit"])]
+        stats: bool,
+
+        /// Print new calls as they are recorded; with a branch, exit when its session ends
+        #[arg(long, short, conflicts_with_all = ["hook_type", "limit", "stats"])]
+        follow: bool,
+    },
+
+    /// Show the captured output of a session's tmux pane
+    Logs {
+        /// Branch name or project:branch (killed entries included)
+        branch: String,
+
+        /// Project to look the branch up in (default: the current repo)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Keep printing output as it is written
+        #[arg(long, short)]
+        follow: bool,
+
+        /// Print escape sequences as captured instead of stripping them
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Track existing worktrees (and their tmux windows) that wortex did not create
+    Adopt {
+        /// Worktree path to adopt (without it, lists unknown worktrees)
+        path: Option<PathBuf>,
+
+        /// Adopt every unknown worktree of the current repo
+        #[arg(long, conflicts_with = "path")]
+        all: bool,
+
+        /// Remote used to derive the project prefix [config: new.remote, default: origin]
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Inspect and edit configuration (~/.wortex/config.toml and .wortex.toml)
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
     },
 }
 
+#[derive(Subcommand)]
+pub enum ToolsCommands {
+    /// Write tool calls with their session's metadata to stdout
+    Export {
+        /// Branch name or project:branch (optional, exports all sessions if not specified)
+        branch: Option<String>,
+
+        /// Project to look the branch up in (default: the current repo); without a branch, export only this project's calls
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: ExportFormat,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the effective value of a key
+    Get {
+        /// Key, e.g. new.base
+        key: String,
+    },
+
+    /// Set a key in the global config (or the repo's .wortex.toml with --repo)
+    Set {
+        /// Key, e.g. new.base
+        key: String,
+
+        /// Value
+        value: String,
+
+        /// Write to .wortex.toml at the repo root instead of the global config
+        #[arg(long)]
+        repo: bool,
+    },
+
+    /// Remove a key from the global config (or the repo's .wortex.toml with --repo)
+    Unset {
+        /// Key, e.g. new.base
+        key: String,
+
+        /// Remove from .wortex.toml at the repo root instead of the global config
+        #[arg(long)]
+        repo: bool,
+    },
+
+    /// List all keys with their effective values and where they come from
+    List,
+}
+
 #[derive(Debug, Clone)]
 pub enum ExitKillArg {
     /// Kill on exit code 0
@@ -154,4 +490,191 @@ impl ExitKillArg {
             }
         }
     }
+
+    /// Strict form of `parse` for a given value: "any", a comma-separated
+    /// list of codes, or empty (exit 0). Anything else is an error.
+    pub fn parse_text(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(ExitKillArg::Default);
+        }
+        if s.eq_ignore_ascii_case("any") {
+            return Ok(ExitKillArg::Any);
+        }
+        s.split(',')
+            .map(|c| c.trim().parse().ok())
+            .collect::<Option<Vec<i32>>>()
+            .map(ExitKillArg::Codes)
+            .ok_or_else(|| {
+                format!(
+                    "invalid exit_kill '{}' (expected \"any\" or codes like \"0,1\")",
+                    s
+                )
+            })
+    }
+}
+
+/// A `--flag`/`--no-flag` pair as an override: `None` when neither was
+/// given, so the config value applies.
+pub fn flag(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a duration given as seconds (`90`) or with a unit suffix
+/// (`30s`, `10m`, `2h`, `1d`).
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 90, 30s, 10m, 2h)", s))?;
+    let scale = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid duration unit '{}' (expected s, m, h or d)", unit)),
+    };
+    number
+        .checked_mul(scale)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration '{}' is too large", s))
+}
+
+/// Parse a point in time: a duration before now (`30m`, `2h`), an RFC 3339
+/// timestamp, or a local date with an optional time (`2025-01-31`,
+/// `2025-01-31 14:00`, `2025-01-31T14:00:05`).
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+    if let Ok(duration) = parse_duration(s) {
+        let too_far = || format!("time '{}' is too far in the past", s);
+        let ago = chrono::Duration::from_std(duration).map_err(|_| too_far())?;
+        return Utc::now().checked_sub_signed(ago).ok_or_else(too_far);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            format!(
+                "invalid time '{}' (e.g. 30m, 2h, 1d, 2025-01-31, 2025-01-31 14:00)",
+                s
+            )
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("'{}' does not exist in the local time zone", s))
+}
+
+fn parse_status(s: &str) -> Result<ProcessStatus, String> {
+    ProcessStatus::parse(s).ok_or_else(|| {
+        format!(
+            "invalid status '{}' (expected spawned, running, waiting, exited, killed, signaled or external)",
+            s
+        )
+    })
+}
+
+/// Check that a pattern compiles; it is matched in SQL later.
+fn parse_regex(s: &str) -> Result<String, String> {
+    regex::Regex::new(s)
+        .map(|_| s.to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        let two_hours_ago = parse_time("2h").unwrap();
+        let age = Utc::now() - two_hours_ago;
+        assert!(age >= chrono::Duration::hours(2) && age < chrono::Duration::minutes(121));
+
+        assert_eq!(
+            parse_time("2025-01-31T14:00:00+02:00").unwrap(),
+            Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap()
+        );
+        let local = |y, m, d, h, min| {
+            Local
+                .with_ymd_and_hms(y, m, d, h, min, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(parse_time("2025-01-31").unwrap(), local(2025, 1, 31, 0, 0));
+        assert_eq!(parse_time("2025-01-31 14:30").unwrap(), local(2025, 1, 31, 14, 30));
+        assert_eq!(parse_time("2025-01-31T14:30:00").unwrap(), local(2025, 1, 31, 14, 30));
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("99999999999d").is_err());
+        assert!(parse_time("2025-13-01").is_err());
+    }
+
+    #[test]
+    fn test_negated_flags_override_config() {
+        let parse = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Commands::List { json, no_json } => flag(json, no_json),
+            _ => unreachable!(),
+        };
+        assert_eq!(parse(&["wortex", "list"]), None);
+        assert_eq!(parse(&["wortex", "list", "--json"]), Some(true));
+        assert_eq!(parse(&["wortex", "list", "--no-json"]), Some(false));
+        // The last one given wins
+        assert_eq!(parse(&["wortex", "list", "--json", "--no-json"]), Some(false));
+        assert_eq!(parse(&["wortex", "list", "--no-json", "--json"]), Some(true));
+    }
+
+    #[test]
+    fn test_files_since_needs_overlap() {
+        assert!(Cli::try_parse_from(["wortex", "files", "--overlap", "--since", "2h"]).is_ok());
+        assert!(Cli::try_parse_from(["wortex", "files", "--since", "2h"]).is_err());
+        assert!(Cli::try_parse_from(["wortex", "files", "f1", "--since", "2h"]).is_err());
+    }
+
+    #[test]
+    fn test_exit_kill_parse_text() {
+        assert!(matches!(ExitKillArg::parse_text(""), Ok(ExitKillArg::Default)));
+        assert!(matches!(ExitKillArg::parse_text("ANY"), Ok(ExitKillArg::Any)));
+        assert!(matches!(
+            ExitKillArg::parse_text("0, 2"),
+            Ok(ExitKillArg::Codes(codes)) if codes == vec![0, 2]
+        ));
+        assert!(ExitKillArg::parse_text("anyy").is_err());
+        assert!(ExitKillArg::parse_text("0,,1").is_err());
+    }
+
+    #[test]
+    fn test_parse_status_and_regex() {
+        assert_eq!(parse_status("exited"), Ok(ProcessStatus::Exited));
+        assert!(parse_status("done").is_err());
+        assert_eq!(parse_regex("cargo (test|build)"), Ok("cargo (test|build)".to_string()));
+        assert!(parse_regex("(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("999999999999999d").is_err());
+    }
 }
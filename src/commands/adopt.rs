@@ -0,0 +1,235 @@
+use crate::error::{Error, Result};
+use crate::git::{self, Worktree};
+use crate::state::{self, Command, Entry, ProcessStatus};
+use crate::tmux::{self, WindowInfo};
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+pub struct AdoptArgs {
+    /// Worktree to adopt; `None` lists (or with `all`, adopts) every unknown worktree
+    pub path: Option<PathBuf>,
+    pub all: bool,
+    /// Remote used to derive the project prefix
+    pub remote: String,
+}
+
+pub fn execute(args: AdoptArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    if !git::is_git_repo(&current_dir) {
+        return Err(Error::NotGitRepo);
+    }
+    let repo_path = git::main_repo_root(&current_dir)?;
+    let git_common_dir = git::git_common_dir(&current_dir)?;
+
+    let worktrees = git::list_worktrees(&repo_path)?;
+    let tracked: HashSet<PathBuf> = state::load()?
+        .entries
+        .iter()
+        .map(|e| canonical(&e.path))
+        .collect();
+    let candidates = unknown_worktrees(&worktrees, &tracked);
+
+    let selected: Vec<&Worktree> = match args.path {
+        Some(ref path) => {
+            let path = canonical(path);
+            if tracked.contains(&path) {
+                return Err(Error::AlreadyTracked(path));
+            }
+            let wt = candidates
+                .iter()
+                .find(|wt| canonical(&wt.path) == path)
+                .ok_or(Error::NotAWorktree(path))?;
+            vec![*wt]
+        }
+        None if args.all => candidates,
+        None => {
+            if candidates.is_empty() {
+                println!("No unknown worktrees found.");
+            } else {
+                println!("Unknown worktrees:");
+                for wt in &candidates {
+                    println!(
+                        "  {} ({})",
+                        wt.path.display(),
+                        wt.branch.as_deref().unwrap_or("detached")
+                    );
+                }
+                println!();
+                println!("Run `wortex adopt <path>` or `wortex adopt --all` to track them.");
+            }
+            return Ok(());
+        }
+    };
+
+    if selected.is_empty() {
+        println!("No unknown worktrees found.");
+        return Ok(());
+    }
+
+    let project = git::current_project(&repo_path, &args.remote).ok_or(Error::NotGitRepo)?;
+    let windows = tmux::list_all_windows().unwrap_or_default();
+    let current_session = if tmux::is_inside_tmux() {
+        tmux::get_current_session().unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    let mut adopted = 0;
+    for wt in selected {
+        let Some(ref branch) = wt.branch else {
+            println!("Skipping {} (detached HEAD)", wt.path.display());
+            continue;
+        };
+
+        if state::find_in_repo(&git_common_dir, &project, branch)?.is_some() {
+            println!("Skipping {} (entry for '{}' already exists)", wt.path.display(), branch);
+            continue;
+        }
+
+        let (tmux_session, tmux_window) = match match_window(&windows, &wt.path, branch) {
+            Some(w) => (w.session.clone(), w.window.clone()),
+            None => (current_session.clone(), branch.clone()),
+        };
+
+        let now = Utc::now();
+        let entry = Entry {
+            id: Uuid::new_v4(),
+            project: project.clone(),
+            branch: branch.clone(),
+            path: wt.path.clone(),
+            tmux_session,
+            tmux_window,
+            command: Command::External,
+            exit_kill: None,
+            exit_code: None,
+            status: ProcessStatus::External,
+            pid: None,
+            blocked_on: None,
+            repo_path: Some(repo_path.clone()),
+            git_common_dir: Some(git_common_dir.clone()),
+            remote: Some(args.remote.clone()),
+            base: None,
+            created_branch: false,
+            removed_at: None,
+            run_count: 0,
+            pushed_sha: None,
+            pushed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        state::add_entry(entry.clone())?;
+        println!(
+            "Adopted '{}' at {} (window {}:{})",
+            branch,
+            wt.path.display(),
+            entry.tmux_session,
+            entry.tmux_window
+        );
+        adopted += 1;
+    }
+
+    println!("Adopted {} worktree(s)", adopted);
+    Ok(())
+}
+
+/// Linked worktrees (not the main one, not bare) that no entry points at.
+fn unknown_worktrees<'a>(worktrees: &'a [Worktree], tracked: &HashSet<PathBuf>) -> Vec<&'a Worktree> {
+    worktrees
+        .iter()
+        .skip(1)
+        .filter(|wt| !wt.bare && !tracked.contains(&canonical(&wt.path)))
+        .collect()
+}
+
+/// Find the window belonging to a worktree: a pane sitting in the worktree
+/// (or below it) wins, otherwise a window named after the branch.
+fn match_window<'a>(windows: &'a [WindowInfo], path: &Path, branch: &str) -> Option<&'a WindowInfo> {
+    let path = canonical(path);
+    windows
+        .iter()
+        .find(|w| canonical(&w.pane_path) == path)
+        .or_else(|| windows.iter().find(|w| canonical(&w.pane_path).starts_with(&path)))
+        .or_else(|| windows.iter().find(|w| w.window == branch))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wt(path: &str, branch: Option<&str>) -> Worktree {
+        Worktree {
+            path: PathBuf::from(path),
+            head: None,
+            branch: branch.map(String::from),
+            bare: false,
+        }
+    }
+
+    fn window(session: &str, name: &str, path: &str) -> WindowInfo {
+        WindowInfo {
+            session: session.to_string(),
+            window: name.to_string(),
+            pane_path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn test_unknown_worktrees_skips_main_and_tracked() {
+        let worktrees = vec![
+            wt("/nonexistent/repo", Some("main")),
+            wt("/nonexistent/mp-a", Some("a")),
+            wt("/nonexistent/mp-b", Some("b")),
+        ];
+        let tracked: HashSet<PathBuf> = [PathBuf::from("/nonexistent/mp-a")].into_iter().collect();
+
+        let unknown = unknown_worktrees(&worktrees, &tracked);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].branch.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_unknown_worktrees_skips_bare() {
+        let mut bare = wt("/nonexistent/mp-bare", None);
+        bare.bare = true;
+        let worktrees = vec![wt("/nonexistent/repo", Some("main")), bare];
+
+        assert!(unknown_worktrees(&worktrees, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_match_window_by_exact_path() {
+        let windows = vec![
+            window("dev", "feature-a", "/nonexistent/elsewhere"),
+            window("dev", "editor", "/nonexistent/mp-a"),
+        ];
+        let found = match_window(&windows, Path::new("/nonexistent/mp-a"), "feature-a").unwrap();
+        assert_eq!(found.window, "editor");
+    }
+
+    #[test]
+    fn test_match_window_by_subdirectory() {
+        let windows = vec![window("dev", "shell", "/nonexistent/mp-a/src")];
+        let found = match_window(&windows, Path::new("/nonexistent/mp-a"), "a").unwrap();
+        assert_eq!(found.window, "shell");
+    }
+
+    #[test]
+    fn test_match_window_falls_back_to_branch_name() {
+        let windows = vec![window("work", "feature-a", "/nonexistent/elsewhere")];
+        let found = match_window(&windows, Path::new("/nonexistent/mp-a"), "feature-a").unwrap();
+        assert_eq!(found.session, "work");
+    }
+
+    #[test]
+    fn test_match_window_none() {
+        let windows = vec![window("dev", "other", "/nonexistent/elsewhere")];
+        assert!(match_window(&windows, Path::new("/nonexistent/mp-a"), "feature-a").is_none());
+    }
+}
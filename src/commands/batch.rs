@@ -0,0 +1,370 @@
+use crate::cli::ExitKillArg;
+use crate::commands::new::{self, NewArgs, Plan};
+use crate::error::{Error, Result};
+use crate::git;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct BatchArgs {
+    pub manifest: PathBuf,
+    pub remote: String,
+    /// Used for tasks that do not set their own (from config)
+    pub agent: Option<String>,
+    pub base: String,
+    pub exit_kill: Option<String>,
+    pub worktree_dir: Option<PathBuf>,
+    pub path_template: String,
+}
+
+/// A manifest is either a list of tasks or a document with a `tasks` list.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    List(Vec<Task>),
+    Document { tasks: Vec<Task> },
+}
+
+/// One session to create; fields mirror the `wortex new` flags.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Task {
+    pub branch: String,
+    pub prompt: Option<String>,
+    pub cmd: Option<String>,
+    pub agent: Option<String>,
+    pub base: Option<String>,
+    pub exit_kill: Option<ExitKillSpec>,
+    #[serde(default)]
+    pub existing: bool,
+}
+
+/// `exit_kill` as written in a manifest: `true` (exit 0), `false` (never),
+/// a code, a list of codes, or the `--exit-kill` syntax ("any", "0,1").
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ExitKillSpec {
+    Flag(bool),
+    Code(i32),
+    Codes(Vec<i32>),
+    Text(String),
+}
+
+impl ExitKillSpec {
+    /// The equivalent `--exit-kill` argument.
+    fn to_arg(&self) -> Option<Option<String>> {
+        match self {
+            ExitKillSpec::Flag(true) => Some(None),
+            ExitKillSpec::Flag(false) => None,
+            ExitKillSpec::Code(code) => Some(Some(code.to_string())),
+            ExitKillSpec::Codes(codes) => Some(Some(
+                codes
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )),
+            ExitKillSpec::Text(text) => Some(Some(text.clone())),
+        }
+    }
+}
+
+/// Per-task result shown in the final table.
+enum Outcome {
+    Created,
+    Failed(String),
+    /// Not attempted because validation failed
+    Skipped,
+}
+
+pub fn execute(args: BatchArgs) -> Result<()> {
+    let tasks = load_manifest(&args.manifest)?;
+    if tasks.is_empty() {
+        return Err(Error::Manifest(format!(
+            "{}: no tasks",
+            args.manifest.display()
+        )));
+    }
+    let ctx = new::repo_context(&args.remote)?;
+
+    // Validate every task before creating anything
+    let checks: Vec<std::result::Result<(), String>> =
+        tasks.iter().map(|t| check_exit_kill(t, &args)).collect();
+    let new_args: Vec<NewArgs> = tasks.into_iter().map(|t| to_new_args(t, &args)).collect();
+    let branches: Vec<String> = new_args.iter().map(|a| a.branch.clone()).collect();
+    let mut plans: Vec<std::result::Result<Plan, String>> = new_args
+        .iter()
+        .zip(checks)
+        .map(|(a, check)| check.and_then(|()| new::plan(a, &ctx).map_err(|e| e.to_string())))
+        .collect();
+    check_conflicts(&new_args, &mut plans);
+
+    let invalid = plans.iter().filter(|p| p.is_err()).count();
+    if invalid > 0 {
+        let outcomes: Vec<Outcome> = plans
+            .into_iter()
+            .map(|p| match p {
+                Ok(_) => Outcome::Skipped,
+                Err(e) => Outcome::Failed(e),
+            })
+            .collect();
+        print_table(&branches, &outcomes);
+        return Err(Error::BatchFailed(invalid, new_args.len()));
+    }
+
+    println!("Fetching from {}...", args.remote);
+    git::fetch(&ctx.repo_path, &args.remote)?;
+
+    // Create each task; a failure does not undo the ones already created
+    let mut outcomes = Vec::new();
+    for (task_args, plan) in new_args.into_iter().zip(plans) {
+        let plan = plan.expect("validated above");
+        println!();
+        outcomes.push(match new::create(task_args, &ctx, plan) {
+            Ok(()) => Outcome::Created,
+            Err(e) => Outcome::Failed(e.to_string()),
+        });
+    }
+
+    println!();
+    print_table(&branches, &outcomes);
+
+    let failed = outcomes
+        .iter()
+        .filter(|o| matches!(o, Outcome::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(Error::BatchFailed(failed, outcomes.len()));
+    }
+    Ok(())
+}
+
+/// Read a manifest: JSON for `.json` files, YAML otherwise.
+fn load_manifest(path: &PathBuf) -> Result<Vec<Task>> {
+    let content = fs::read_to_string(path)?;
+    let is_json = path.extension().is_some_and(|e| e == "json");
+    parse_manifest(&content, is_json)
+        .map_err(|e| Error::Manifest(format!("{}: {}", path.display(), e)))
+}
+
+fn parse_manifest(content: &str, is_json: bool) -> std::result::Result<Vec<Task>, String> {
+    let manifest: Manifest = if is_json {
+        serde_json::from_str(content).map_err(|e| e.to_string())?
+    } else {
+        serde_yaml::from_str(content).map_err(|e| e.to_string())?
+    };
+    Ok(match manifest {
+        Manifest::List(tasks) | Manifest::Document { tasks } => tasks,
+    })
+}
+
+/// The task's `--exit-kill` argument, falling back to `new.exit_kill`.
+fn exit_kill_arg(task: &Task, args: &BatchArgs) -> Option<Option<String>> {
+    match &task.exit_kill {
+        Some(spec) => spec.to_arg(),
+        None => args.exit_kill.clone().map(Some),
+    }
+}
+
+/// Reject `exit_kill` text that `ExitKillArg::parse` would quietly read as
+/// exit 0.
+fn check_exit_kill(task: &Task, args: &BatchArgs) -> std::result::Result<(), String> {
+    match exit_kill_arg(task, args) {
+        Some(Some(text)) => ExitKillArg::parse_text(&text).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn to_new_args(task: Task, args: &BatchArgs) -> NewArgs {
+    let exit_kill = exit_kill_arg(&task, args);
+    NewArgs {
+        branch: task.branch,
+        prompt: task.prompt,
+        cmd: task.cmd,
+        agent: task.agent.or_else(|| args.agent.clone()),
+        exit_kill: ExitKillArg::parse(exit_kill),
+        remote: args.remote.clone(),
+        base: task.base.unwrap_or_else(|| args.base.clone()),
+        worktree_dir: args.worktree_dir.clone(),
+        path_template: args.path_template.clone(),
+        existing: task.existing,
+    }
+}
+
+/// Tasks that are valid on their own but collide with an earlier task: same
+/// branch, or the same worktree directory.
+fn check_conflicts(tasks: &[NewArgs], plans: &mut [std::result::Result<Plan, String>]) {
+    let mut branches: HashMap<&str, usize> = HashMap::new();
+    let mut paths: HashMap<PathBuf, usize> = HashMap::new();
+
+    for (i, task) in tasks.iter().enumerate() {
+        if let Some(first) = branches.get(task.branch.as_str()) {
+            plans[i] = Err(format!(
+                "branch '{}' is also task #{}",
+                task.branch,
+                first + 1
+            ));
+            continue;
+        }
+        branches.insert(&task.branch, i);
+
+        if let Ok(plan) = &plans[i] {
+            if let Some(first) = paths.get(&plan.worktree_path) {
+                plans[i] = Err(format!(
+                    "worktree '{}' is also used by task #{}",
+                    plan.worktree_path.display(),
+                    first + 1
+                ));
+            } else {
+                paths.insert(plan.worktree_path.clone(), i);
+            }
+        }
+    }
+}
+
+fn print_table(branches: &[String], outcomes: &[Outcome]) {
+    println!("{:<4} {:<30} {:<8} DETAIL", "#", "BRANCH", "RESULT");
+    for (i, (branch, outcome)) in branches.iter().zip(outcomes).enumerate() {
+        let (result, detail) = match outcome {
+            Outcome::Created => ("created", ""),
+            Outcome::Failed(e) => ("failed", e.trim()),
+            Outcome::Skipped => ("skipped", "not created: other tasks are invalid"),
+        };
+        println!("{:<4} {:<30} {:<8} {}", i + 1, branch, result, detail);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_args() -> BatchArgs {
+        BatchArgs {
+            manifest: PathBuf::from("tasks.yaml"),
+            remote: "origin".to_string(),
+            agent: Some("worker".to_string()),
+            base: "main".to_string(),
+            exit_kill: None,
+            worktree_dir: None,
+            path_template: "{project}-{branch_slug}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_yaml_list() {
+        let yaml = "
+- branch: fix-ci
+  prompt: Fix the CI pipeline
+  exit_kill: true
+- branch: bump-deps
+  cmd: cargo update
+  base: develop
+  exit_kill: [0, 1]
+";
+        let tasks = parse_manifest(yaml, false).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].branch, "fix-ci");
+        assert_eq!(tasks[0].prompt.as_deref(), Some("Fix the CI pipeline"));
+        assert_eq!(tasks[0].exit_kill, Some(ExitKillSpec::Flag(true)));
+        assert_eq!(tasks[1].base.as_deref(), Some("develop"));
+        assert_eq!(tasks[1].exit_kill, Some(ExitKillSpec::Codes(vec![0, 1])));
+    }
+
+    #[test]
+    fn test_parse_json_document() {
+        let json =
+            r#"{"tasks": [{"branch": "a", "cmd": "true", "exit_kill": "any", "existing": true}]}"#;
+        let tasks = parse_manifest(json, true).unwrap();
+        assert_eq!(
+            tasks[0].exit_kill,
+            Some(ExitKillSpec::Text("any".to_string()))
+        );
+        assert!(tasks[0].existing);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_field() {
+        assert!(parse_manifest("- branch: a\n  promt: typo\n", false).is_err());
+        assert!(parse_manifest("- prompt: no branch\n", false).is_err());
+    }
+
+    #[test]
+    fn test_exit_kill_spec_to_arg() {
+        assert_eq!(ExitKillSpec::Flag(true).to_arg(), Some(None));
+        assert_eq!(ExitKillSpec::Flag(false).to_arg(), None);
+        assert_eq!(ExitKillSpec::Code(1).to_arg(), Some(Some("1".to_string())));
+        assert_eq!(
+            ExitKillSpec::Codes(vec![0, 2]).to_arg(),
+            Some(Some("0,2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_check_exit_kill() {
+        let task = |yaml: &str| -> Task { serde_yaml::from_str(yaml).unwrap() };
+        let args = batch_args();
+        assert!(check_exit_kill(&task("branch: a\nexit_kill: any\n"), &args).is_ok());
+        assert!(check_exit_kill(&task("branch: a\nexit_kill: \"0, 1\"\n"), &args).is_ok());
+        assert!(check_exit_kill(&task("branch: a\nexit_kill: true\n"), &args).is_ok());
+        let err = check_exit_kill(&task("branch: a\nexit_kill: anyy\n"), &args).unwrap_err();
+        assert!(err.contains("anyy"), "{}", err);
+        assert!(check_exit_kill(&task("branch: a\nexit_kill: \"0,x\"\n"), &args).is_err());
+
+        // A malformed config default is caught too, unless the task overrides it
+        let args = BatchArgs {
+            exit_kill: Some("al".to_string()),
+            ..batch_args()
+        };
+        assert!(check_exit_kill(&task("branch: a\n"), &args).is_err());
+        assert!(check_exit_kill(&task("branch: a\nexit_kill: false\n"), &args).is_ok());
+    }
+
+    #[test]
+    fn test_task_overrides_defaults() {
+        let args = BatchArgs {
+            exit_kill: Some("any".to_string()),
+            ..batch_args()
+        };
+        let task: Task =
+            serde_yaml::from_str("branch: a\nprompt: p\nagent: reviewer\nbase: develop\n").unwrap();
+        let new_args = to_new_args(task, &args);
+        assert_eq!(new_args.agent.as_deref(), Some("reviewer"));
+        assert_eq!(new_args.base, "develop");
+        assert!(matches!(new_args.exit_kill, Some(ExitKillArg::Any)));
+
+        let task: Task = serde_yaml::from_str("branch: b\ncmd: c\nexit_kill: false\n").unwrap();
+        let new_args = to_new_args(task, &args);
+        assert_eq!(new_args.agent.as_deref(), Some("worker"));
+        assert_eq!(new_args.base, "main");
+        assert!(new_args.exit_kill.is_none());
+    }
+
+    #[test]
+    fn test_check_conflicts() {
+        let args = batch_args();
+        let tasks: Vec<NewArgs> = ["a", "b", "a", "c"]
+            .iter()
+            .map(|b| {
+                let task: Task = serde_yaml::from_str(&format!("branch: {}\ncmd: x\n", b)).unwrap();
+                to_new_args(task, &args)
+            })
+            .collect();
+        let plan = |p: &str| {
+            Ok(Plan {
+                id: uuid::Uuid::new_v4(),
+                worktree_path: PathBuf::from(p),
+            })
+        };
+        let mut plans = vec![plan("/wt/a"), plan("/wt/b"), plan("/wt/a2"), plan("/wt/b")];
+
+        check_conflicts(&tasks, &mut plans);
+        assert!(plans[0].is_ok());
+        assert!(plans[1].is_ok());
+        assert_eq!(
+            plans[2].as_ref().err().unwrap(),
+            "branch 'a' is also task #1"
+        );
+        assert!(plans[3].as_ref().err().unwrap().contains("task #2"));
+    }
+}
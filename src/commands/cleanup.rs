@@ -1,7 +1,8 @@
 use crate::error::Result;
-use crate::state::{self, Entry};
-use crate::tmux;
+use crate::state::{self, Command, Entry};
+use crate::{git, tmux};
 use std::collections::HashSet;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -11,20 +12,33 @@ pub struct StaleEntry {
     pub reasons: Vec<String>,
 }
 
-pub fn execute(dry_run: bool) -> Result<()> {
+pub fn execute(dry_run: bool, purge: bool) -> Result<()> {
     let state = state::load()?;
 
     let stale_entries = find_stale_entries(
         &state.entries,
         |e| e.path.exists(),
-        |e| tmux::window_exists(&e.tmux_session, &e.tmux_window).unwrap_or(false),
+        // Adopted entries may legitimately have no window
+        |e| {
+            matches!(e.command, Command::External)
+                || tmux::window_exists(&e.tmux_session, &e.tmux_window).unwrap_or(false)
+        },
     );
 
     if stale_entries.is_empty() {
         println!("No stale entries found.");
-        return Ok(());
+    } else {
+        remove_stale(&state.entries, stale_entries, dry_run)?;
+    }
+
+    if purge {
+        purge_archived(dry_run)?;
     }
 
+    Ok(())
+}
+
+fn remove_stale(entries: &[Entry], stale_entries: Vec<StaleEntry>, dry_run: bool) -> Result<()> {
     println!("Found {} stale entries:", stale_entries.len());
     for entry in &stale_entries {
         println!("  {} ({})", entry.branch, entry.reasons.join(", "));
@@ -33,8 +47,25 @@ pub fn execute(dry_run: bool) -> Result<()> {
     if dry_run {
         println!("\nDry run - no changes made.");
     } else {
-        for entry in stale_entries {
-            state::remove_entry(entry.id)?;
+        let mut repos: HashSet<PathBuf> = HashSet::new();
+        for stale in stale_entries {
+            if let Some(entry) = entries.iter().find(|e| e.id == stale.id)
+                && !entry.path.exists()
+            {
+                match entry.repo_dir() {
+                    Ok(repo) => {
+                        repos.insert(repo);
+                    }
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+            }
+            state::archive_entry(stale.id)?;
+        }
+        // Forget deleted worktrees so their paths and branches can be reused
+        for repo in repos {
+            if let Err(e) = git::prune_worktrees(&repo) {
+                eprintln!("Warning: {}", e);
+            }
         }
         println!("\nRemoved stale entries from state.");
     }
@@ -42,18 +73,38 @@ pub fn execute(dry_run: bool) -> Result<()> {
     Ok(())
 }
 
+/// Drop archived entries for good, along with their tool calls and logs.
+fn purge_archived(dry_run: bool) -> Result<()> {
+    let archived = state::load_archived()?;
+    if archived.is_empty() {
+        println!("No archived entries to purge.");
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Would purge {} archived entries.", archived.len());
+        return Ok(());
+    }
+
+    for entry in &archived {
+        state::purge_entry(entry.id)?;
+    }
+    println!("Purged {} archived entries.", archived.len());
+    Ok(())
+}
+
 /// Finds stale entries based on provided check functions.
 /// An entry is stale if:
 /// - path_exists returns false
 /// - window_exists returns false
-/// - it's a duplicate branch (second or later occurrence)
+/// - it's a duplicate branch within its project (second or later occurrence)
 pub fn find_stale_entries<F, G>(entries: &[Entry], path_exists: F, window_exists: G) -> Vec<StaleEntry>
 where
     F: Fn(&Entry) -> bool,
     G: Fn(&Entry) -> bool,
 {
     let mut stale_entries: Vec<StaleEntry> = Vec::new();
-    let mut seen_branches: HashSet<&str> = HashSet::new();
+    let mut seen_branches: HashSet<(&str, &str)> = HashSet::new();
 
     for entry in entries {
         let mut reasons: Vec<String> = Vec::new();
@@ -64,7 +115,7 @@ where
         if !window_exists(entry) {
             reasons.push("window missing".to_string());
         }
-        if seen_branches.contains(entry.branch.as_str()) {
+        if seen_branches.contains(&(entry.project.as_str(), entry.branch.as_str())) {
             reasons.push("duplicate branch".to_string());
         }
 
@@ -76,7 +127,7 @@ where
             });
         }
 
-        seen_branches.insert(&entry.branch);
+        seen_branches.insert((&entry.project, &entry.branch));
     }
 
     stale_entries
@@ -85,24 +136,14 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{Command, Entry};
-    use chrono::Utc;
+    use crate::state::Entry;
     use std::path::PathBuf;
 
     fn make_entry(id: Uuid, branch: &str) -> Entry {
         Entry {
             id,
-            project: "test".to_string(),
-            branch: branch.to_string(),
             path: PathBuf::from("/tmp/test"),
-            tmux_session: "0".to_string(),
-            tmux_window: branch.to_string(),
-            command: Command::Raw {
-                cmd: "echo test".to_string(),
-            },
-            exit_kill: None,
-            exit_code: None,
-            created_at: Utc::now(),
+            ..Entry::for_test("test", branch)
         }
     }
 
@@ -172,6 +213,17 @@ mod tests {
         assert!(stale[0].reasons.contains(&"duplicate branch".to_string()));
     }
 
+    #[test]
+    fn test_same_branch_in_other_project_not_duplicate() {
+        let mut other = make_entry(Uuid::new_v4(), "feature-a");
+        other.project = "other".to_string();
+        let entries = vec![make_entry(Uuid::new_v4(), "feature-a"), other];
+
+        let stale = find_stale_entries(&entries, |_| true, |_| true);
+
+        assert!(stale.is_empty());
+    }
+
     #[test]
     fn test_first_valid_duplicate_with_missing_path_still_kept() {
         // First entry has valid path/window, second is duplicate
@@ -0,0 +1,94 @@
+use crate::config::{self, ConfigKey, KEYS};
+use crate::error::{Error, Result};
+use std::env;
+use std::path::PathBuf;
+
+pub enum ConfigAction {
+    Get { key: String },
+    Set { key: String, value: String, repo: bool },
+    Unset { key: String, repo: bool },
+    List,
+}
+
+pub fn execute(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => get(&key),
+        ConfigAction::Set { key, value, repo } => set(&key, &value, repo),
+        ConfigAction::Unset { key, repo } => unset(&key, repo),
+        ConfigAction::List => list(),
+    }
+}
+
+/// Raw tables for each layer, highest precedence first.
+fn layers() -> Result<Vec<(&'static str, toml::Table)>> {
+    let mut layers = Vec::new();
+    if let Some(path) = config::repo_config_path(&env::current_dir()?) {
+        layers.push(("repo", config::read_table(&path)?));
+    }
+    layers.push(("global", config::read_table(&config::global_config_path()?)?));
+    Ok(layers)
+}
+
+/// Effective value of a key and the layer it came from.
+fn resolve(layers: &[(&'static str, toml::Table)], key: &ConfigKey) -> Option<(String, &'static str)> {
+    layers
+        .iter()
+        .find_map(|(source, table)| {
+            config::get_value(table, key.name).map(|v| (config::display_value(v), *source))
+        })
+        .or_else(|| key.default.map(|d| (d.to_string(), "default")))
+}
+
+fn target_path(repo: bool) -> Result<PathBuf> {
+    if repo {
+        config::repo_config_path(&env::current_dir()?).ok_or(Error::NotGitRepo)
+    } else {
+        config::global_config_path()
+    }
+}
+
+fn get(key: &str) -> Result<()> {
+    let key = config::find_key(key)?;
+    match resolve(&layers()?, key) {
+        Some((value, _)) => {
+            println!("{}", value);
+            Ok(())
+        }
+        None => Err(Error::Config(format!("'{}' is not set", key.name))),
+    }
+}
+
+fn set(key: &str, value: &str, repo: bool) -> Result<()> {
+    let key = config::find_key(key)?;
+    let path = target_path(repo)?;
+    let mut table = config::read_table(&path)?;
+    config::set_value(&mut table, key, value)?;
+    config::write_table(&path, &table)?;
+    println!("Set {} = {} in {}", key.name, value, path.display());
+    Ok(())
+}
+
+fn unset(key: &str, repo: bool) -> Result<()> {
+    let key = config::find_key(key)?;
+    let path = target_path(repo)?;
+    let mut table = config::read_table(&path)?;
+    if config::unset_value(&mut table, key.name) {
+        config::write_table(&path, &table)?;
+        println!("Unset {} in {}", key.name, path.display());
+    } else {
+        println!("{} is not set in {}", key.name, path.display());
+    }
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let layers = layers()?;
+
+    println!("{:<22} {:<30} {:<8}", "KEY", "VALUE", "SOURCE");
+    for key in KEYS {
+        let (value, source) = resolve(&layers, key).unwrap_or(("-".to_string(), "unset"));
+        println!("{:<22} {:<30} {:<8}", key.name, value, source);
+    }
+
+    Ok(())
+}
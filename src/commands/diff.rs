@@ -0,0 +1,199 @@
+use crate::error::{Error, Result};
+use crate::git;
+use crate::state::{self, ProjectScope};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+pub struct DiffArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    pub format: DiffFormat,
+    /// Per-file additions and deletions as JSON (ignores `format`)
+    pub json: bool,
+    /// Used when the entry has no recorded remote or base (from config)
+    pub remote: String,
+    pub base: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffFormat {
+    Patch,
+    Stat,
+    NameOnly,
+}
+
+impl DiffFormat {
+    pub fn from_flags(stat: bool, name_only: bool) -> Self {
+        if stat {
+            DiffFormat::Stat
+        } else if name_only {
+            DiffFormat::NameOnly
+        } else {
+            DiffFormat::Patch
+        }
+    }
+
+    fn git_args(&self) -> &'static [&'static str] {
+        match self {
+            DiffFormat::Patch => &[],
+            DiffFormat::Stat => &["--stat"],
+            DiffFormat::NameOnly => &["--name-only"],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct FileChange {
+    path: String,
+    /// `None` for binary files
+    additions: Option<u64>,
+    deletions: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiffSummary {
+    branch: String,
+    base: String,
+    merge_base: String,
+    additions: u64,
+    deletions: u64,
+    files: Vec<FileChange>,
+}
+
+pub fn execute(args: DiffArgs) -> Result<()> {
+    let entry = state::resolve(&args.branch, &args.scope)?;
+    if !entry.path.exists() {
+        return Err(Error::WorktreeMissing(entry.path.clone()));
+    }
+
+    let base_ref = format!(
+        "{}/{}",
+        entry.remote.as_deref().unwrap_or(&args.remote),
+        entry.base.as_deref().unwrap_or(&args.base)
+    );
+    let merge_base = git::merge_base(&entry.path, &base_ref, "HEAD")?;
+
+    if args.json {
+        let files = file_changes(&entry.path, &merge_base)?;
+        let summary = DiffSummary {
+            branch: entry.branch.clone(),
+            base: base_ref,
+            merge_base,
+            additions: files.iter().filter_map(|f| f.additions).sum(),
+            deletions: files.iter().filter_map(|f| f.deletions).sum(),
+            files,
+        };
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    let output = git::diff_working_tree(&entry.path, &merge_base, args.format.git_args())?;
+    if output.is_empty() {
+        println!("No changes relative to {}", base_ref);
+        return Ok(());
+    }
+    match io::stdout().write_all(output.as_bytes()) {
+        // Reader went away (e.g. piped into `head`)
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Committed and uncommitted changes in the worktree since `merge_base`.
+fn file_changes(worktree: &Path, merge_base: &str) -> Result<Vec<FileChange>> {
+    let numstat = git::diff_working_tree(worktree, merge_base, &["--numstat", "--no-renames"])?;
+    Ok(parse_numstat(&numstat))
+}
+
+/// Parse `git diff --numstat` lines: "<added>\t<deleted>\t<path>", with "-"
+/// counts for binary files.
+fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let additions = parts.next()?;
+            let deletions = parts.next()?;
+            let path = parts.next()?;
+            Some(FileChange {
+                path: path.to_string(),
+                additions: additions.parse().ok(),
+                deletions: deletions.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::{commit, repo_with_worktree, run_git};
+    use std::fs;
+
+    #[test]
+    fn test_parse_numstat() {
+        let output = "3\t1\tsrc/main.rs\n-\t-\tlogo.png\n10\t0\tdocs/a b.md\n";
+        assert_eq!(
+            parse_numstat(output),
+            vec![
+                FileChange {
+                    path: "src/main.rs".to_string(),
+                    additions: Some(3),
+                    deletions: Some(1),
+                },
+                FileChange {
+                    path: "logo.png".to_string(),
+                    additions: None,
+                    deletions: None,
+                },
+                FileChange {
+                    path: "docs/a b.md".to_string(),
+                    additions: Some(10),
+                    deletions: Some(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_from_flags() {
+        assert_eq!(DiffFormat::from_flags(false, false), DiffFormat::Patch);
+        assert_eq!(DiffFormat::from_flags(true, false), DiffFormat::Stat);
+        assert_eq!(DiffFormat::from_flags(false, true), DiffFormat::NameOnly);
+    }
+
+    #[test]
+    fn test_changes_include_uncommitted_and_untracked() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+
+        // Base moves on: its changes are not part of the session's diff
+        commit(&repo, "other.txt", "base\n");
+        commit(&worktree, "a.txt", "a\nb\n");
+        fs::write(worktree.join("README.md"), "changed\n").unwrap();
+        fs::write(worktree.join("new.txt"), "new\n").unwrap();
+        fs::write(worktree.join(".gitignore"), "ignored.txt\n").unwrap();
+        fs::write(worktree.join("ignored.txt"), "x\n").unwrap();
+
+        let merge_base = git::merge_base(&worktree, "main", "HEAD").unwrap();
+        let mut paths: Vec<_> = file_changes(&worktree, &merge_base)
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.path, f.additions, f.deletions))
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                (".gitignore".to_string(), Some(1), Some(0)),
+                ("README.md".to_string(), Some(1), Some(1)),
+                ("a.txt".to_string(), Some(2), Some(0)),
+                ("new.txt".to_string(), Some(1), Some(0)),
+            ]
+        );
+
+        // The worktree's real index is untouched
+        assert_eq!(run_git(&worktree, &["diff", "--cached", "--name-only"]), "");
+        assert!(run_git(&worktree, &["status", "--porcelain"]).contains("?? new.txt"));
+    }
+}
@@ -0,0 +1,463 @@
+use crate::db::{self, ToolCall, ToolCallFilter};
+use crate::error::{Error, Result};
+use crate::state::{self, Command, Entry, ProjectScope};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+use uuid::Uuid;
+
+pub struct ExportArgs {
+    pub branch: Option<String>,
+    pub scope: ProjectScope,
+    /// `--project` as given; without a branch it exports only this project
+    pub project: Option<String>,
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON object per tool call
+    Jsonl,
+    /// One row per tool call, with a header
+    Csv,
+    /// OTLP/JSON trace export: a trace per session, a span per tool call
+    OtlpJson,
+}
+
+/// A tool call with the metadata of its session, as written by the jsonl and
+/// csv formats. `I` is the tool input: parsed JSON for jsonl, the raw text
+/// for csv.
+#[derive(Debug, Serialize)]
+struct Record<'a, I: Serialize> {
+    id: i64,
+    session_id: Uuid,
+    project: Option<&'a str>,
+    branch: Option<&'a str>,
+    agent: Option<&'a str>,
+    prompt: Option<&'a str>,
+    cmd: Option<&'a str>,
+    session_status: Option<&'static str>,
+    exit_code: Option<i32>,
+    sequence: i64,
+    tool_name: &'a str,
+    hook_type: &'a str,
+    tool_use_id: Option<&'a str>,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    duration_ms: Option<i64>,
+    is_error: Option<bool>,
+    decision: Option<&'a str>,
+    decision_reason: Option<&'a str>,
+    tool_input: I,
+    tool_response: Option<&'a str>,
+}
+
+impl<'a> Record<'a, &'a str> {
+    fn new(call: &'a ToolCall, entry: Option<&'a Entry>) -> Self {
+        let (agent, prompt, cmd) = match entry.map(|e| &e.command) {
+            Some(Command::Claude { prompt, agent }) => (agent.as_deref(), Some(prompt.as_str()), None),
+            Some(Command::Raw { cmd }) => (None, None, Some(cmd.as_str())),
+            Some(Command::External) | None => (None, None, None),
+        };
+        Record {
+            id: call.id,
+            session_id: call.process_id,
+            project: entry.map(|e| e.project.as_str()),
+            branch: entry.map(|e| e.branch.as_str()),
+            agent,
+            prompt,
+            cmd,
+            session_status: entry.map(|e| e.status.as_str()),
+            exit_code: entry.and_then(|e| e.exit_code),
+            sequence: call.sequence,
+            tool_name: &call.tool_name,
+            hook_type: &call.hook_type,
+            tool_use_id: call.tool_use_id.as_deref(),
+            started_at: call.timestamp,
+            ended_at: call.ended_at,
+            duration_ms: call.duration_ms,
+            is_error: call.is_error,
+            decision: call.decision.as_deref(),
+            decision_reason: call.decision_reason.as_deref(),
+            tool_input: &call.tool_input,
+            tool_response: call.tool_response.as_deref(),
+        }
+    }
+
+    /// The same record with the input parsed; input that is not JSON stays a string.
+    fn with_json_input(self) -> Record<'a, Value> {
+        let input = serde_json::from_str(self.tool_input)
+            .unwrap_or_else(|_| Value::String(self.tool_input.to_string()));
+        Record {
+            id: self.id,
+            session_id: self.session_id,
+            project: self.project,
+            branch: self.branch,
+            agent: self.agent,
+            prompt: self.prompt,
+            cmd: self.cmd,
+            session_status: self.session_status,
+            exit_code: self.exit_code,
+            sequence: self.sequence,
+            tool_name: self.tool_name,
+            hook_type: self.hook_type,
+            tool_use_id: self.tool_use_id,
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            duration_ms: self.duration_ms,
+            is_error: self.is_error,
+            decision: self.decision,
+            decision_reason: self.decision_reason,
+            tool_input: input,
+            tool_response: self.tool_response,
+        }
+    }
+}
+
+pub fn execute(args: ExportArgs) -> Result<()> {
+    let conn = db::open_and_init()?;
+    let filter = ToolCallFilter {
+        process_id: match args.branch {
+            Some(ref branch) => Some(state::resolve_including_removed(branch, &args.scope)?.id),
+            None => None,
+        },
+        project: args.project,
+        ..Default::default()
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let result = match args.format {
+        ExportFormat::Jsonl => write_jsonl(&conn, &filter, &mut out),
+        ExportFormat::Csv => write_csv(&conn, &filter, &mut out),
+        ExportFormat::OtlpJson => write_otlp(&conn, &filter, &mut out),
+    }
+    .and_then(|()| out.flush().map_err(Error::from));
+    match result {
+        // Reader went away (e.g. piped into `head`)
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// Session metadata, looked up once per session as calls stream past.
+struct Sessions<'c> {
+    conn: &'c Connection,
+    entries: HashMap<Uuid, Option<Entry>>,
+}
+
+impl<'c> Sessions<'c> {
+    fn new(conn: &'c Connection) -> Self {
+        Sessions {
+            conn,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, id: Uuid) -> Result<Option<&Entry>> {
+        if !self.entries.contains_key(&id) {
+            let entry = db::get_process_by_id(self.conn, id)?;
+            self.entries.insert(id, entry);
+        }
+        Ok(self.entries[&id].as_ref())
+    }
+}
+
+fn write_jsonl(conn: &Connection, filter: &ToolCallFilter, out: &mut impl Write) -> Result<()> {
+    let mut sessions = Sessions::new(conn);
+    db::for_each_tool_call(conn, filter, |call| {
+        let record = Record::new(&call, sessions.get(call.process_id)?).with_json_input();
+        serde_json::to_writer(&mut *out, &record)?;
+        writeln!(out)?;
+        Ok(())
+    })
+}
+
+fn write_csv(conn: &Connection, filter: &ToolCallFilter, out: &mut impl Write) -> Result<()> {
+    let mut sessions = Sessions::new(conn);
+    let mut writer = csv::Writer::from_writer(out);
+    db::for_each_tool_call(conn, filter, |call| {
+        writer
+            .serialize(Record::new(&call, sessions.get(call.process_id)?))
+            .map_err(csv_error)
+    })?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => Error::Io(e),
+        kind => Error::Io(io::Error::other(format!("{:?}", kind))),
+    }
+}
+
+/// One OTLP/JSON document. Sessions are written one at a time, each as a
+/// `resourceSpans` element whose resource carries the session metadata.
+fn write_otlp(conn: &Connection, filter: &ToolCallFilter, out: &mut impl Write) -> Result<()> {
+    write!(out, "{{\"resourceSpans\":[")?;
+    for (i, process_id) in db::tool_call_sessions(conn, filter)?.into_iter().enumerate() {
+        let session_filter = ToolCallFilter {
+            process_id: Some(process_id),
+            ..filter.clone()
+        };
+        let calls = db::get_tool_calls(conn, &session_filter, None)?;
+        let entry = db::get_process_by_id(conn, process_id)?;
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        serde_json::to_writer(&mut *out, &resource_spans(process_id, entry.as_ref(), &calls))?;
+    }
+    writeln!(out, "]}}")?;
+    Ok(())
+}
+
+/// OTLP span kind INTERNAL.
+const SPAN_KIND_INTERNAL: u8 = 1;
+
+/// OTLP status codes.
+const STATUS_UNSET: u8 = 0;
+const STATUS_OK: u8 = 1;
+const STATUS_ERROR: u8 = 2;
+
+/// A session as a trace: the trace id is the entry's UUID, a root span covers
+/// the session and every tool call is a child span of it.
+fn resource_spans(process_id: Uuid, entry: Option<&Entry>, calls: &[ToolCall]) -> Value {
+    let trace_id = process_id.simple().to_string();
+    let root_id = trace_id[16..].to_string();
+
+    let mut resource = vec![
+        attribute("service.name", json!({"stringValue": "wortex"})),
+        attribute("wortex.session.id", json!({"stringValue": process_id.to_string()})),
+    ];
+    let mut name = format!("session {}", process_id);
+    let mut start = calls.first().map(|c| c.timestamp);
+    let mut end = calls.iter().map(|c| c.ended_at.unwrap_or(c.timestamp)).max();
+    let mut status = json!({"code": STATUS_UNSET});
+
+    if let Some(entry) = entry {
+        name = format!("session {}:{}", entry.project, entry.branch);
+        for (key, value) in [
+            ("wortex.project", entry.project.as_str()),
+            ("wortex.branch", entry.branch.as_str()),
+            ("wortex.session.status", entry.status.as_str()),
+        ] {
+            resource.push(attribute(key, json!({"stringValue": value})));
+        }
+        match &entry.command {
+            Command::Claude { prompt, agent } => {
+                resource.push(attribute("wortex.prompt", json!({"stringValue": prompt})));
+                if let Some(agent) = agent {
+                    resource.push(attribute("wortex.agent", json!({"stringValue": agent})));
+                }
+            }
+            Command::Raw { cmd } => {
+                resource.push(attribute("wortex.cmd", json!({"stringValue": cmd})));
+            }
+            Command::External => {}
+        }
+        if let Some(code) = entry.exit_code {
+            resource.push(attribute("wortex.exit_code", json!({"intValue": code.to_string()})));
+            status = if code == 0 {
+                json!({"code": STATUS_OK})
+            } else {
+                json!({"code": STATUS_ERROR, "message": format!("exit code {}", code)})
+            };
+            // A finished session ends when its status was last updated
+            end = end.max(Some(entry.updated_at));
+        }
+        start = Some(start.map_or(entry.created_at, |s| s.min(entry.created_at)));
+    }
+
+    let mut spans = Vec::with_capacity(calls.len() + 1);
+    spans.push(json!({
+        "traceId": trace_id,
+        "spanId": root_id,
+        "name": name,
+        "kind": SPAN_KIND_INTERNAL,
+        "startTimeUnixNano": unix_nanos(start),
+        "endTimeUnixNano": unix_nanos(end),
+        "status": status,
+    }));
+    spans.extend(calls.iter().map(|call| tool_span(&trace_id, &root_id, call)));
+
+    json!({
+        "resource": {"attributes": resource},
+        "scopeSpans": [{
+            "scope": {"name": "wortex", "version": env!("CARGO_PKG_VERSION")},
+            "spans": spans,
+        }],
+    })
+}
+
+fn tool_span(trace_id: &str, parent_id: &str, call: &ToolCall) -> Value {
+    let mut attributes = vec![
+        attribute("tool.name", json!({"stringValue": call.tool_name})),
+        attribute("tool.input", json!({"stringValue": call.tool_input})),
+        attribute("tool.sequence", json!({"intValue": call.sequence.to_string()})),
+    ];
+    if let Some(ref tool_use_id) = call.tool_use_id {
+        attributes.push(attribute("tool.use_id", json!({"stringValue": tool_use_id})));
+    }
+    if let Some(ref response) = call.tool_response {
+        attributes.push(attribute("tool.response", json!({"stringValue": response})));
+    }
+    if let Some(ref decision) = call.decision {
+        attributes.push(attribute("tool.decision", json!({"stringValue": decision})));
+    }
+    if let Some(ref reason) = call.decision_reason {
+        attributes.push(attribute("tool.decision_reason", json!({"stringValue": reason})));
+    }
+    let status = match (call.decision.as_deref(), call.is_error) {
+        (Some("deny"), _) => json!({"code": STATUS_ERROR, "message": "denied by policy"}),
+        (_, Some(true)) => json!({"code": STATUS_ERROR, "message": "tool call failed"}),
+        (_, Some(false)) => json!({"code": STATUS_OK}),
+        (_, None) => json!({"code": STATUS_UNSET}),
+    };
+
+    json!({
+        "traceId": trace_id,
+        "spanId": format!("{:016x}", call.id),
+        "parentSpanId": parent_id,
+        "name": call.tool_name,
+        "kind": SPAN_KIND_INTERNAL,
+        "startTimeUnixNano": unix_nanos(Some(call.timestamp)),
+        "endTimeUnixNano": unix_nanos(Some(call.ended_at.unwrap_or(call.timestamp))),
+        "attributes": attributes,
+        "status": status,
+    })
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({"key": key, "value": value})
+}
+
+/// OTLP/JSON encodes 64-bit integers as strings.
+fn unix_nanos(time: Option<DateTime<Utc>>) -> String {
+    time.and_then(|t| t.timestamp_nanos_opt())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ProcessStatus;
+    use chrono::TimeZone;
+
+    fn entry(command: Command, exit_code: Option<i32>) -> Entry {
+        Entry {
+            command,
+            exit_code,
+            status: if exit_code.is_some() {
+                ProcessStatus::Exited
+            } else {
+                ProcessStatus::Running
+            },
+            run_count: 1,
+            created_at: Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 1, 1, 12, 5, 0).unwrap(),
+            ..Entry::for_test("proj", "feature")
+        }
+    }
+
+    fn call(id: i64, process_id: Uuid, tool_name: &str, input: &str, is_error: Option<bool>) -> ToolCall {
+        let timestamp = Utc.with_ymd_and_hms(2025, 1, 1, 12, 1, id as u32).unwrap();
+        ToolCall {
+            id,
+            process_id,
+            hook_type: if is_error.is_some() { "post" } else { "pre" }.to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: input.to_string(),
+            timestamp,
+            sequence: id,
+            tool_use_id: Some(format!("toolu_{}", id)),
+            ended_at: is_error.map(|_| timestamp + chrono::Duration::milliseconds(250)),
+            duration_ms: is_error.map(|_| 250),
+            is_error,
+            tool_response: None,
+            decision: None,
+            decision_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_record_carries_session_metadata() {
+        let entry = entry(
+            Command::Claude {
+                prompt: "fix the bug".to_string(),
+                agent: Some("reviewer".to_string()),
+            },
+            Some(0),
+        );
+        let call = call(1, entry.id, "Bash", r#"{"command":"ls"}"#, Some(false));
+
+        let value = serde_json::to_value(Record::new(&call, Some(&entry)).with_json_input()).unwrap();
+        assert_eq!(value["branch"], "feature");
+        assert_eq!(value["prompt"], "fix the bug");
+        assert_eq!(value["agent"], "reviewer");
+        assert_eq!(value["cmd"], Value::Null);
+        assert_eq!(value["exit_code"], 0);
+        assert_eq!(value["session_status"], "exited");
+        assert_eq!(value["tool_input"]["command"], "ls");
+
+        let raw = ToolCall {
+            tool_input: "not json".to_string(),
+            ..call.clone()
+        };
+        let value = serde_json::to_value(Record::new(&raw, None).with_json_input()).unwrap();
+        assert_eq!(value["tool_input"], "not json");
+        assert_eq!(value["branch"], Value::Null);
+    }
+
+    #[test]
+    fn test_csv_quotes_input() {
+        let entry = entry(Command::Raw { cmd: "make test".to_string() }, None);
+        let call = call(1, entry.id, "Write", "{\"content\":\"a,b\\nc\"}", None);
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.serialize(Record::new(&call, Some(&entry))).unwrap();
+        let text = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let mut lines = text.lines();
+        assert!(lines.next().unwrap().starts_with("id,session_id,project,branch,agent,prompt,cmd,"));
+        let row = lines.next().unwrap();
+        assert!(row.contains(",make test,running,,1,Write,pre,toolu_1,"));
+        assert!(row.ends_with(r#","{""content"":""a,b\nc""}","#));
+    }
+
+    #[test]
+    fn test_resource_spans_is_one_trace_per_session() {
+        let entry = entry(Command::Raw { cmd: "make".to_string() }, Some(2));
+        let calls = vec![
+            call(7, entry.id, "Read", "{}", Some(false)),
+            call(9, entry.id, "Bash", "{}", Some(true)),
+        ];
+
+        let value = resource_spans(entry.id, Some(&entry), &calls);
+        let attributes = value["resource"]["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&attribute("wortex.branch", json!({"stringValue": "feature"}))));
+        assert!(attributes.contains(&attribute("wortex.exit_code", json!({"intValue": "2"}))));
+
+        let spans = value["scopeSpans"][0]["spans"].as_array().unwrap();
+        assert_eq!(spans.len(), 3);
+        let trace_id = entry.id.simple().to_string();
+        assert!(spans.iter().all(|s| s["traceId"] == trace_id.as_str()));
+
+        let root = &spans[0];
+        assert_eq!(root["name"], "session proj:feature");
+        assert_eq!(root["status"]["code"], STATUS_ERROR);
+        assert_eq!(root["startTimeUnixNano"], "1735732800000000000");
+        assert_eq!(root["endTimeUnixNano"], "1735733100000000000");
+
+        assert_eq!(spans[1]["spanId"], "0000000000000007");
+        assert_eq!(spans[1]["parentSpanId"], root["spanId"]);
+        assert_eq!(spans[1]["status"]["code"], STATUS_OK);
+        assert_eq!(spans[1]["startTimeUnixNano"], "1735732867000000000");
+        assert_eq!(spans[1]["endTimeUnixNano"], "1735732867250000000");
+        assert_eq!(spans[2]["name"], "Bash");
+        assert_eq!(spans[2]["status"]["code"], STATUS_ERROR);
+    }
+}
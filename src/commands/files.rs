@@ -0,0 +1,206 @@
+use crate::db::{self, FileAccess, SharedEdit};
+use crate::error::Result;
+use crate::state::{self, ProjectScope};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+pub struct FilesArgs {
+    /// Required unless `overlap` is set
+    pub branch: Option<String>,
+    pub scope: ProjectScope,
+    /// List files modified by more than one live session instead
+    pub overlap: bool,
+    /// With `overlap`: only count edits made since then
+    pub since: Option<DateTime<Utc>>,
+    pub json: bool,
+}
+
+/// A file modified in several sessions of one repo.
+#[derive(Debug, PartialEq, Serialize)]
+struct Overlap {
+    repo: String,
+    project: String,
+    path: String,
+    sessions: Vec<OverlapSession>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct OverlapSession {
+    id: Uuid,
+    branch: String,
+    edits: u64,
+    last_edit_at: Option<DateTime<Utc>>,
+}
+
+pub fn execute(args: FilesArgs) -> Result<()> {
+    let conn = db::open_and_init()?;
+
+    if args.overlap {
+        let overlaps = group(db::shared_edits(&conn, args.since)?);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&overlaps)?);
+        } else {
+            print_overlaps(&overlaps);
+        }
+        return Ok(());
+    }
+
+    let branch = args.branch.as_deref().unwrap_or_default();
+    let entry = state::resolve_including_removed(branch, &args.scope)?;
+    let files = db::file_access(&conn, entry.id)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&files)?);
+    } else {
+        print_files(&files);
+    }
+    Ok(())
+}
+
+fn print_files(files: &[FileAccess]) {
+    if files.is_empty() {
+        println!("No files read or modified.");
+        return;
+    }
+
+    println!("{:>4} {:>6} {:>7}  FILE", "READ", "EDITED", "WRITTEN");
+    for file in files {
+        println!(
+            "{:>4} {:>6} {:>7}  {}",
+            file.read, file.edited, file.written, file.path
+        );
+    }
+
+    let modified = files.iter().filter(|f| f.edited + f.written > 0).count();
+    println!();
+    println!(
+        "{} file(s) modified, {} only read",
+        modified,
+        files.len() - modified
+    );
+}
+
+fn print_overlaps(overlaps: &[Overlap]) {
+    if overlaps.is_empty() {
+        println!("No file is modified by more than one live session.");
+        return;
+    }
+
+    println!(
+        "{:<8} {:<30} {:<20} {:>5}  LAST EDIT",
+        "PROJECT", "FILE", "BRANCH", "EDITS"
+    );
+    for overlap in overlaps {
+        for (i, session) in overlap.sessions.iter().enumerate() {
+            let (project, path) = if i == 0 {
+                (overlap.project.as_str(), overlap.path.as_str())
+            } else {
+                ("", "")
+            };
+            let last_edit = session
+                .last_edit_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "{:<8} {:<30} {:<20} {:>5}  {}",
+                project, path, session.branch, session.edits, last_edit
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "{} file(s) modified by more than one live session",
+        overlaps.len()
+    );
+}
+
+/// Group per-session rows, already ordered by repo and path, by file.
+fn group(edits: Vec<SharedEdit>) -> Vec<Overlap> {
+    let mut overlaps: Vec<Overlap> = Vec::new();
+    for edit in edits {
+        let session = OverlapSession {
+            id: edit.process_id,
+            branch: edit.branch,
+            edits: edit.edits,
+            last_edit_at: edit.last_edit_at,
+        };
+        match overlaps.last_mut() {
+            Some(last) if last.repo == edit.repo && last.path == edit.path => {
+                last.sessions.push(session);
+            }
+            _ => overlaps.push(Overlap {
+                repo: edit.repo,
+                project: edit.project,
+                path: edit.path,
+                sessions: vec![session],
+            }),
+        }
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(project: &str, path: &str, branch: &str) -> SharedEdit {
+        SharedEdit {
+            repo: format!("/repos/{}/.git", project),
+            project: project.to_string(),
+            path: path.to_string(),
+            process_id: Uuid::nil(),
+            branch: branch.to_string(),
+            edits: 1,
+            last_edit_at: None,
+        }
+    }
+
+    #[test]
+    fn test_group_by_repo_and_path() {
+        let overlaps = group(vec![
+            edit("a", "src/lib.rs", "one"),
+            edit("a", "src/lib.rs", "two"),
+            edit("a", "src/main.rs", "one"),
+            edit("a", "src/main.rs", "three"),
+            edit("b", "src/main.rs", "x"),
+            edit("b", "src/main.rs", "y"),
+        ]);
+
+        let summary: Vec<_> = overlaps
+            .iter()
+            .map(|o| {
+                let branches: Vec<_> = o.sessions.iter().map(|s| s.branch.as_str()).collect();
+                (o.project.as_str(), o.path.as_str(), branches)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "src/lib.rs", vec!["one", "two"]),
+                ("a", "src/main.rs", vec!["one", "three"]),
+                ("b", "src/main.rs", vec!["x", "y"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_same_prefix_in_two_repos_is_not_merged() {
+        let mut elsewhere = edit("a", "src/lib.rs", "three");
+        elsewhere.repo = "/elsewhere/a/.git".to_string();
+        let overlaps = group(vec![
+            edit("a", "src/lib.rs", "one"),
+            edit("a", "src/lib.rs", "two"),
+            elsewhere.clone(),
+            SharedEdit {
+                branch: "four".to_string(),
+                ..elsewhere
+            },
+        ]);
+        let repos: Vec<_> = overlaps
+            .iter()
+            .map(|o| (o.repo.as_str(), o.sessions.len()))
+            .collect();
+        assert_eq!(repos, vec![("/repos/a/.git", 2), ("/elsewhere/a/.git", 2)]);
+    }
+}
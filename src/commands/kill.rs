@@ -1,11 +1,19 @@
-use crate::error::{Error, Result};
-use crate::state;
+use crate::error::Result;
+use crate::state::{self, Command, Entry, ProjectScope};
 use crate::{git, tmux};
+use std::path::Path;
 
-pub fn execute(branch: &str, keep_worktree: bool) -> Result<()> {
+pub fn execute(branch: &str, scope: &ProjectScope, keep_worktree: bool) -> Result<()> {
     // Find the entry
-    let entry = state::find_by_branch(branch)?
-        .ok_or_else(|| Error::EntryNotFound(branch.to_string()))?;
+    let entry = state::resolve(branch, scope)?;
+    teardown(&entry, keep_worktree)
+}
+
+/// Kill the window, remove the worktree and branch, and archive the entry.
+/// With `keep_worktree` the branch is kept too.
+pub fn teardown(entry: &Entry, keep_worktree: bool) -> Result<()> {
+    // Before anything is torn down: without its repo there is nothing safe to do
+    let repo = entry.repo_dir()?;
 
     // Kill tmux window if exists
     if tmux::window_exists(&entry.tmux_session, &entry.tmux_window)? {
@@ -13,21 +21,96 @@ pub fn execute(branch: &str, keep_worktree: bool) -> Result<()> {
         tmux::kill_window(&entry.tmux_session, &entry.tmux_window)?;
     }
 
-    // Remove worktree unless --keep-worktree
+    remove_checkout(entry, &repo, keep_worktree)?;
+
+    // Archive in state (keeps the log reachable via `wortex logs`)
+    state::archive_entry(entry.id)?;
+
+    println!("Killed worktree for branch '{}' ({})", entry.branch, entry.project);
+    Ok(())
+}
+
+/// Remove the worktree unless `keep_worktree`, then the local branch if
+/// wortex created it. A branch checked out with `--existing` belongs to the
+/// user and may hold unpushed work; an adopted worktree is left alone entirely.
+fn remove_checkout(entry: &Entry, repo: &Path, keep_worktree: bool) -> Result<()> {
+    if matches!(entry.command, Command::External) {
+        println!(
+            "Leaving adopted worktree at {:?} and branch '{}' in place",
+            entry.path, entry.branch
+        );
+        return Ok(());
+    }
+
     if !keep_worktree && entry.path.exists() {
         println!("Removing worktree at {:?}...", entry.path);
-        git::remove_worktree(&entry.path)?;
+        git::remove_worktree(repo, &entry.path)?;
     }
 
-    // Delete local branch
-    if git::branch_exists(&entry.branch)? {
+    // A kept worktree still has the branch checked out
+    if keep_worktree || !git::branch_exists(repo, &entry.branch)? {
+        return Ok(());
+    }
+    if entry.created_branch {
         println!("Deleting local branch '{}'...", entry.branch);
-        git::delete_branch(&entry.branch)?;
+        git::delete_branch(repo, &entry.branch)?;
+    } else {
+        println!("Keeping branch '{}' (not created by wortex)", entry.branch);
     }
+    Ok(())
+}
 
-    // Remove from state
-    state::remove_entry(entry.id)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::{commit, repo_with_worktree, run_git};
 
-    println!("Killed worktree for branch '{}'", branch);
-    Ok(())
+    fn entry_for(repo: &Path, worktree: &Path, created_branch: bool) -> Entry {
+        Entry {
+            path: worktree.to_path_buf(),
+            repo_path: Some(repo.to_path_buf()),
+            created_branch,
+            ..Entry::for_test("repo", "feature")
+        }
+    }
+
+    #[test]
+    fn test_created_branch_is_deleted() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        remove_checkout(&entry_for(&repo, &worktree, true), &repo, false).unwrap();
+        assert!(!worktree.exists());
+        assert!(!git::branch_exists(&repo, "feature").unwrap());
+    }
+
+    #[test]
+    fn test_existing_branch_survives_kill() {
+        // `wortex new feature --existing` on a branch with unpushed work
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "work.txt", "unpushed\n");
+        let head = run_git(&worktree, &["rev-parse", "HEAD"]);
+
+        remove_checkout(&entry_for(&repo, &worktree, false), &repo, false).unwrap();
+        assert!(!worktree.exists());
+        assert_eq!(run_git(&repo, &["rev-parse", "feature"]), head);
+    }
+
+    #[test]
+    fn test_adopted_worktree_is_left_alone() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        let entry = Entry {
+            command: Command::External,
+            ..entry_for(&repo, &worktree, false)
+        };
+        remove_checkout(&entry, &repo, false).unwrap();
+        assert!(worktree.exists());
+        assert!(git::branch_exists(&repo, "feature").unwrap());
+    }
+
+    #[test]
+    fn test_kept_worktree_keeps_branch() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        remove_checkout(&entry_for(&repo, &worktree, true), &repo, true).unwrap();
+        assert!(worktree.exists());
+        assert!(git::branch_exists(&repo, "feature").unwrap());
+    }
 }
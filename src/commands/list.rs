@@ -1,5 +1,6 @@
+use crate::commands::push;
 use crate::error::Result;
-use crate::state;
+use crate::state::{self, Entry, ProcessStatus};
 use crate::tmux;
 
 pub fn execute(json: bool) -> Result<()> {
@@ -17,22 +18,21 @@ pub fn execute(json: bool) -> Result<()> {
 
     // Print header
     println!(
-        "{:<20} {:<25} {:<40} {:<10} {:<5}",
-        "BRANCH", "TMUX", "PATH", "STATUS", "EXIT"
+        "{:<8} {:<20} {:<25} {:<40} {:<10} {:<8} {:<5} {:<10}",
+        "PROJECT", "BRANCH", "TMUX", "PATH", "STATUS", "PID", "EXIT", "PUSH"
     );
 
     for entry in &state.entries {
-        // Check if window still exists
-        let window_exists =
-            tmux::window_exists(&entry.tmux_session, &entry.tmux_window).unwrap_or(false);
-
-        let status = if entry.exit_code.is_some() {
-            "exited"
-        } else if window_exists {
-            "running"
-        } else {
-            "stale"
-        };
+        let status = display_status(
+            entry,
+            |e| tmux::window_exists(&e.tmux_session, &e.tmux_window).unwrap_or(false),
+            state::pid_alive,
+        );
+
+        let pid_str = entry
+            .pid
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "-".to_string());
 
         let exit_str = entry
             .exit_code
@@ -47,14 +47,101 @@ pub fn execute(json: bool) -> Result<()> {
             .to_string_lossy()
             .replace(dirs::home_dir().unwrap().to_str().unwrap(), "~");
 
+        let push_str = push::push_state(entry).label();
+
         println!(
-            "{:<20} {:<25} {:<40} {:<10} {:<5}",
-            entry.branch, tmux_target, path_display, status, exit_str
+            "{:<8} {:<20} {:<25} {:<40} {:<10} {:<8} {:<5} {:<10}",
+            entry.project, entry.branch, tmux_target, path_display, status, pid_str, exit_str,
+            push_str
         );
+        if let (ProcessStatus::Waiting, Some(reason)) = (entry.status, &entry.blocked_on) {
+            println!("{:<8} waiting on: {}", "", reason);
+        }
     }
 
     println!();
-    println!("Tip: Use `wortex switch <branch>` or `tmux select-window -t <session>:<window>`");
+    println!("Tip: Use `wortex switch [project:]<branch>` or `tmux select-window -t <session>:<window>`");
 
     Ok(())
 }
+
+/// Status shown in the table. The recorded status is authoritative; it is only
+/// downgraded when the recorded state can no longer be true:
+/// - "stale": never started and its window is gone
+/// - "lost": recorded as running or waiting but the PID is gone (supervisor
+///   died before it could record the exit)
+///
+/// Waiting entries are shown as "WAITING" so they stand out.
+fn display_status<F, G>(entry: &Entry, window_exists: F, pid_alive: G) -> &'static str
+where
+    F: Fn(&Entry) -> bool,
+    G: Fn(u32) -> bool,
+{
+    match entry.status {
+        ProcessStatus::Spawned if !window_exists(entry) => "stale",
+        ProcessStatus::Running | ProcessStatus::Waiting => match entry.pid {
+            Some(pid) if !pid_alive(pid) => "lost",
+            _ if entry.status == ProcessStatus::Waiting => "WAITING",
+            _ => "running",
+        },
+        status => status.as_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(status: ProcessStatus, pid: Option<u32>) -> Entry {
+        Entry {
+            status,
+            pid,
+            ..Entry::for_test("test", "feature-a")
+        }
+    }
+
+    #[test]
+    fn test_running_with_live_pid() {
+        let entry = make_entry(ProcessStatus::Running, Some(42));
+        assert_eq!(display_status(&entry, |_| true, |_| true), "running");
+    }
+
+    #[test]
+    fn test_running_with_dead_pid_is_lost() {
+        let entry = make_entry(ProcessStatus::Running, Some(42));
+        assert_eq!(display_status(&entry, |_| true, |_| false), "lost");
+    }
+
+    #[test]
+    fn test_running_ignores_window_state() {
+        // Status comes from the database, not from tmux
+        let entry = make_entry(ProcessStatus::Running, Some(42));
+        assert_eq!(display_status(&entry, |_| false, |_| true), "running");
+    }
+
+    #[test]
+    fn test_waiting_stands_out() {
+        let entry = make_entry(ProcessStatus::Waiting, Some(42));
+        assert_eq!(display_status(&entry, |_| true, |_| true), "WAITING");
+        assert_eq!(display_status(&entry, |_| true, |_| false), "lost");
+    }
+
+    #[test]
+    fn test_spawned_without_window_is_stale() {
+        let entry = make_entry(ProcessStatus::Spawned, None);
+        assert_eq!(display_status(&entry, |_| false, |_| true), "stale");
+        assert_eq!(display_status(&entry, |_| true, |_| true), "spawned");
+    }
+
+    #[test]
+    fn test_final_statuses_pass_through() {
+        for status in [
+            ProcessStatus::Exited,
+            ProcessStatus::Killed,
+            ProcessStatus::Signaled,
+        ] {
+            let entry = make_entry(status, Some(42));
+            assert_eq!(display_status(&entry, |_| false, |_| false), status.as_str());
+        }
+    }
+}
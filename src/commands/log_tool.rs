@@ -1,7 +1,13 @@
 use crate::db;
 use crate::error::{Error, Result};
+use crate::notify;
+use crate::policy::{self, Action, Decision};
+use crate::state::ProcessStatus;
+use rusqlite::Connection;
 use serde::Deserialize;
+use std::env;
 use std::io::{self, Read};
+use std::path::PathBuf;
 use uuid::Uuid;
 
 /// Claude hook input structure for PreToolUse/PostToolUse
@@ -10,16 +16,33 @@ use uuid::Uuid;
 pub struct HookInput {
     pub tool_name: String,
     pub tool_input: serde_json::Value,
-    // PostToolUse also includes tool_output, but we only log inputs
+    /// Identifies one invocation; shared by its PreToolUse and PostToolUse hooks
+    #[serde(default)]
+    pub tool_use_id: Option<String>,
+    /// Only present for PostToolUse
+    #[serde(default)]
+    pub tool_response: Option<serde_json::Value>,
+    /// Directory Claude runs the hook in
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
 }
 
+/// Claude hook input for Notification (permission prompts, idle waiting)
+#[derive(Debug, Deserialize)]
+pub struct NotificationInput {
+    pub message: String,
+}
+
+/// Event kind recorded when the agent starts waiting on the user.
+pub const EVENT_WAITING: &str = "waiting";
+
 pub fn execute(session_id: &str, hook_type: &str) -> Result<()> {
     // Parse session ID as UUID
     let process_id = Uuid::parse_str(session_id)
         .map_err(|_| Error::EntryNotFound(session_id.to_string()))?;
 
     // Validate hook type
-    if hook_type != "pre" && hook_type != "post" {
+    if hook_type != "pre" && hook_type != "post" && hook_type != "notification" {
         return Err(Error::InvalidHookType(hook_type.to_string()));
     }
 
@@ -29,21 +52,166 @@ pub fn execute(session_id: &str, hook_type: &str) -> Result<()> {
         .read_to_string(&mut input)
         .map_err(Error::Io)?;
 
+    if hook_type == "pre" {
+        let hook_input: HookInput = serde_json::from_str(&input)?;
+        pre_tool_use(process_id, &hook_input);
+        return Ok(());
+    }
+
+    // Open database (schema auto-created)
+    let conn = db::open_and_init()?;
+
+    if hook_type == "notification" {
+        let notification: NotificationInput = serde_json::from_str(&input)?;
+        db::set_waiting(&conn, process_id, &notification.message)?;
+        db::insert_event(&conn, process_id, EVENT_WAITING, Some(&notification.message))?;
+        if let Some(entry) = db::get_process_by_id(&conn, process_id)?
+            .filter(|e| e.status == ProcessStatus::Waiting)
+        {
+            notify::emit(notify::Event::Waiting, &entry, Some(&notification.message));
+        }
+        return Ok(());
+    }
+
     // Parse the hook input JSON
     let hook_input: HookInput = serde_json::from_str(&input)?;
 
     // Convert tool_input to string for storage
     let input_str = serde_json::to_string(&hook_input.tool_input)?;
 
-    // Open database (schema auto-created)
-    let conn = db::open_and_init()?;
+    // A tool call means the agent got past whatever it was waiting on
+    db::clear_waiting(&conn, process_id)?;
+
+    let tool_use_id = hook_input.tool_use_id.as_deref();
+    let response = hook_input.tool_response.as_ref().map(response_to_string);
+    let is_error = hook_input
+        .tool_response
+        .as_ref()
+        .map(response_is_error)
+        .unwrap_or(false);
+    db::complete_tool_call(
+        &conn,
+        process_id,
+        tool_use_id,
+        &hook_input.tool_name,
+        &input_str,
+        response.as_deref(),
+        is_error,
+    )?;
+
+    Ok(())
+}
+
+/// PreToolUse: apply the policy and print a deny before touching the
+/// database, then record the call and its decision. Recording is best-effort:
+/// a database error must not fail the hook, which would let the call through.
+fn pre_tool_use(process_id: Uuid, hook_input: &HookInput) {
+    let conn = db::open_and_init();
 
-    // Insert tool call into database
-    db::insert_tool_call(&conn, process_id, hook_type, &hook_input.tool_name, &input_str)?;
+    // The session's worktree selects the policy (through its repo config)
+    // and anchors `outside_worktree` rules. Without its entry, fall back to
+    // the directory Claude runs the hook in, which is the worktree too.
+    let worktree = conn
+        .as_ref()
+        .ok()
+        .and_then(|conn| db::get_process_by_id(conn, process_id).ok().flatten())
+        .map(|entry| entry.path)
+        .or_else(|| hook_input.cwd.clone())
+        .or_else(|| env::current_dir().ok());
+    let decision = worktree.and_then(|worktree| {
+        policy::decide(&worktree, &hook_input.tool_name, &hook_input.tool_input)
+    });
+    if let Some(decision) = decision.as_ref().filter(|d| d.action == Action::Deny) {
+        println!("{}", deny_output(decision));
+    }
 
+    if let Err(e) = conn.and_then(|conn| record_pre(&conn, process_id, hook_input, decision)) {
+        eprintln!("Warning: tool call not recorded: {}", e);
+    }
+}
+
+fn record_pre(
+    conn: &Connection,
+    process_id: Uuid,
+    hook_input: &HookInput,
+    decision: Option<Decision>,
+) -> Result<()> {
+    let input_str = serde_json::to_string(&hook_input.tool_input)?;
+
+    // A tool call means the agent got past whatever it was waiting on
+    db::clear_waiting(conn, process_id)?;
+
+    let row_id = db::insert_tool_call(
+        conn,
+        process_id,
+        "pre",
+        &hook_input.tool_name,
+        &input_str,
+        hook_input.tool_use_id.as_deref(),
+    )?;
+    if let Some(decision) = decision {
+        db::set_tool_call_decision(
+            conn,
+            row_id,
+            decision.action.as_str(),
+            decision.reason.as_deref(),
+        )?;
+    }
     Ok(())
 }
 
+/// PreToolUse output that makes Claude skip the call; the reason is shown to
+/// the agent. Allowed calls print nothing and go through Claude's usual
+/// permission checks.
+fn deny_output(decision: &Decision) -> serde_json::Value {
+    serde_json::json!({
+        "hookSpecificOutput": {
+            "hookEventName": "PreToolUse",
+            "permissionDecision": "deny",
+            "permissionDecisionReason": decision.reason,
+        }
+    })
+}
+
+/// Plain string responses are stored as-is, anything else as compact JSON.
+fn response_to_string(response: &serde_json::Value) -> String {
+    match response {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Tool responses have no common schema, so look for the usual failure markers:
+/// `is_error: true`, `success: false`, a non-empty `error`, `interrupted: true`
+/// or a non-zero `exit_code`/`exitCode`.
+fn response_is_error(response: &serde_json::Value) -> bool {
+    let Some(map) = response.as_object() else {
+        return false;
+    };
+
+    let flag = |key: &str| map.get(key).and_then(|v| v.as_bool());
+    if flag("is_error") == Some(true)
+        || flag("success") == Some(false)
+        || flag("interrupted") == Some(true)
+    {
+        return true;
+    }
+
+    let has_error = match map.get("error") {
+        None | Some(serde_json::Value::Null) | Some(serde_json::Value::Bool(false)) => false,
+        Some(serde_json::Value::String(s)) => !s.is_empty(),
+        Some(_) => true,
+    };
+    if has_error {
+        return true;
+    }
+
+    ["exit_code", "exitCode"]
+        .iter()
+        .filter_map(|key| map.get(*key).and_then(|v| v.as_i64()))
+        .any(|code| code != 0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,6 +247,13 @@ mod tests {
         assert_eq!(hook_input.tool_name, "Read");
     }
 
+    #[test]
+    fn test_parse_hook_input_cwd() {
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"ls"},"cwd":"/work/mp-fix"}"#;
+        let hook_input: HookInput = serde_json::from_str(json).unwrap();
+        assert_eq!(hook_input.cwd, Some(PathBuf::from("/work/mp-fix")));
+    }
+
     #[test]
     fn test_parse_hook_input_nested_object() {
         let json = r#"{"tool_name":"Edit","tool_input":{"file_path":"/test.rs","old_string":"fn main()","new_string":"fn main() -> Result<()>"}}"#;
@@ -88,6 +263,73 @@ mod tests {
         assert_eq!(hook_input.tool_input["old_string"], "fn main()");
     }
 
+    #[test]
+    fn test_parse_hook_input_post_tool_use() {
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"false"},"tool_use_id":"toolu_01","tool_response":{"stdout":"","stderr":"boom","interrupted":false}}"#;
+        let hook_input: HookInput = serde_json::from_str(json).unwrap();
+
+        assert_eq!(hook_input.tool_use_id.as_deref(), Some("toolu_01"));
+        assert_eq!(hook_input.tool_response.unwrap()["stderr"], "boom");
+    }
+
+    #[test]
+    fn test_parse_hook_input_without_tool_use_id() {
+        let json = r#"{"tool_name":"Read","tool_input":{"file_path":"/test"}}"#;
+        let hook_input: HookInput = serde_json::from_str(json).unwrap();
+
+        assert!(hook_input.tool_use_id.is_none());
+        assert!(hook_input.tool_response.is_none());
+    }
+
+    #[test]
+    fn test_response_is_error_markers() {
+        assert!(response_is_error(&serde_json::json!({"is_error": true})));
+        assert!(response_is_error(&serde_json::json!({"success": false})));
+        assert!(response_is_error(&serde_json::json!({"interrupted": true})));
+        assert!(response_is_error(&serde_json::json!({"error": "not found"})));
+        assert!(response_is_error(&serde_json::json!({"exit_code": 2})));
+    }
+
+    #[test]
+    fn test_response_is_error_success_shapes() {
+        assert!(!response_is_error(&serde_json::json!({"stdout": "ok", "interrupted": false})));
+        assert!(!response_is_error(&serde_json::json!({"success": true, "error": null})));
+        assert!(!response_is_error(&serde_json::json!({"exit_code": 0})));
+        assert!(!response_is_error(&serde_json::json!("file contents")));
+    }
+
+    #[test]
+    fn test_response_to_string() {
+        assert_eq!(response_to_string(&serde_json::json!("plain")), "plain");
+        assert_eq!(
+            response_to_string(&serde_json::json!({"stdout": "x"})),
+            r#"{"stdout":"x"}"#
+        );
+    }
+
+    #[test]
+    fn test_deny_output() {
+        let decision = Decision {
+            action: Action::Deny,
+            reason: Some("rm -rf is not allowed".to_string()),
+        };
+        let output = deny_output(&decision);
+        assert_eq!(output["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+        assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(
+            output["hookSpecificOutput"]["permissionDecisionReason"],
+            "rm -rf is not allowed"
+        );
+    }
+
+    #[test]
+    fn test_parse_notification_input() {
+        let json = r#"{"session_id":"abc","transcript_path":"/t.jsonl","hook_event_name":"Notification","message":"Claude needs your permission to use Bash"}"#;
+        let notification: NotificationInput = serde_json::from_str(json).unwrap();
+
+        assert_eq!(notification.message, "Claude needs your permission to use Bash");
+    }
+
     #[test]
     fn test_invalid_session_id_format() {
         let result = Uuid::parse_str("not-a-uuid");
@@ -0,0 +1,160 @@
+use crate::error::{Error, Result};
+use crate::state::{self, ProjectScope};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::Duration;
+
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct LogsArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    pub follow: bool,
+    /// Print the captured bytes as-is, escape sequences included
+    pub raw: bool,
+}
+
+pub fn execute(args: LogsArgs) -> Result<()> {
+    // Killed and cleaned-up entries are archived, so their logs stay reachable
+    let entry = state::resolve_including_removed(&args.branch, &args.scope)?;
+    let path = state::log_path(entry.id)?;
+    if !path.exists() {
+        return Err(Error::NoLog(args.branch.clone()));
+    }
+
+    match print_log(&mut File::open(&path)?, args.follow, args.raw) {
+        // Reader went away (e.g. piped into `head`)
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn print_log(file: &mut File, follow: bool, raw: bool) -> Result<()> {
+    let mut out = io::stdout().lock();
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        file.read_to_end(&mut pending)?;
+
+        // Only complete lines are printed while following: an escape sequence
+        // may still be cut in half
+        let split = match pending.iter().rposition(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None if follow => 0,
+            None => pending.len(),
+        };
+        let rest = pending.split_off(split);
+        write_chunk(&mut out, &pending, raw)?;
+        pending = rest;
+
+        if !follow {
+            break;
+        }
+        out.flush()?;
+        thread::sleep(FOLLOW_INTERVAL);
+    }
+
+    write_chunk(&mut out, &pending, raw)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn write_chunk(out: &mut impl Write, chunk: &[u8], raw: bool) -> Result<()> {
+    if raw {
+        out.write_all(chunk)?;
+        return Ok(());
+    }
+    for line in String::from_utf8_lossy(chunk).split_inclusive('\n') {
+        let newline = if line.ends_with('\n') { "\n" } else { "" };
+        write!(out, "{}{}", strip_ansi(line.trim_end_matches('\n')), newline)?;
+    }
+    Ok(())
+}
+
+/// Plain text of one captured line: escape sequences and other control
+/// characters are dropped, and only what follows the last carriage return is
+/// kept (that is what ended up on screen).
+fn strip_ansi(line: &str) -> String {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let line = line.rsplit('\r').next().unwrap_or(line);
+
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters up to a final byte in '@'..='~'
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: terminated by BEL or ESC '\'
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Character set designation takes one more byte
+                Some('(' | ')') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_plain_text_untouched() {
+        assert_eq!(strip_ansi("hello\tworld"), "hello\tworld");
+    }
+
+    #[test]
+    fn test_strip_ansi_colors_and_cursor() {
+        assert_eq!(strip_ansi("\x1b[1;31merror\x1b[0m: boom\x1b[K"), "error: boom");
+        assert_eq!(strip_ansi("\x1b[?25lhidden cursor\x1b[?25h"), "hidden cursor");
+    }
+
+    #[test]
+    fn test_strip_ansi_osc_title() {
+        assert_eq!(strip_ansi("\x1b]0;my title\x07prompt$ "), "prompt$ ");
+        assert_eq!(strip_ansi("\x1b]8;;http://x\x1b\\link"), "link");
+    }
+
+    #[test]
+    fn test_strip_ansi_carriage_return_overwrite() {
+        assert_eq!(strip_ansi("progress 10%\rprogress 100%\r"), "progress 100%");
+    }
+
+    #[test]
+    fn test_write_chunk_strips_per_line() {
+        let mut out = Vec::new();
+        write_chunk(&mut out, b"\x1b[32mok\x1b[0m\r\nnext", false).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ok\nnext");
+    }
+
+    #[test]
+    fn test_write_chunk_raw() {
+        let mut out = Vec::new();
+        write_chunk(&mut out, b"\x1b[32mok\x1b[0m\r\n", true).unwrap();
+        assert_eq!(out, b"\x1b[32mok\x1b[0m\r\n");
+    }
+}
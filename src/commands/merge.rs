@@ -0,0 +1,269 @@
+use crate::commands::kill;
+use crate::error::{Error, Result};
+use crate::git;
+use crate::state::{self, ProjectScope};
+use std::path::Path;
+
+pub struct MergeArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    pub strategy: MergeStrategy,
+    /// Branch to merge into, checked out in the main repo (default: the
+    /// entry's base)
+    pub into: Option<String>,
+    /// Used when the entry has no recorded base (from config)
+    pub base: String,
+    /// Tear the session down after a successful merge, as `wortex kill`
+    pub kill: bool,
+    pub keep_worktree: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    /// `git merge` (fast-forward when possible, else a merge commit)
+    Merge,
+    /// One squashed commit on the base
+    Squash,
+    /// Rebase the branch onto the base in its worktree, then fast-forward
+    Rebase,
+    /// Fast-forward only
+    FfOnly,
+}
+
+impl MergeStrategy {
+    pub fn from_flags(squash: bool, rebase: bool, ff_only: bool) -> Self {
+        if squash {
+            MergeStrategy::Squash
+        } else if rebase {
+            MergeStrategy::Rebase
+        } else if ff_only {
+            MergeStrategy::FfOnly
+        } else {
+            MergeStrategy::Merge
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+            MergeStrategy::FfOnly => "fast-forward",
+        }
+    }
+}
+
+pub fn execute(args: MergeArgs) -> Result<()> {
+    let entry = state::resolve(&args.branch, &args.scope)?;
+    let repo = entry.repo_dir()?;
+    let worktree = Some(entry.path.as_path()).filter(|p| p.exists());
+    let into = args.into.or_else(|| entry.base.clone()).unwrap_or(args.base);
+
+    println!(
+        "Merging '{}' into '{}' ({})...",
+        entry.branch,
+        into,
+        args.strategy.as_str()
+    );
+    integrate(&repo, worktree, &into, &entry.branch, args.strategy)?;
+    println!("Merged '{}' into '{}'", entry.branch, into);
+
+    if args.kill {
+        kill::teardown(&entry, args.keep_worktree)?;
+    }
+    Ok(())
+}
+
+/// Integrate `branch` into `base` in the main repo at `repo`, after checking
+/// that both trees are clean and the result will not conflict. Nothing is
+/// changed when a check fails.
+fn integrate(
+    repo: &Path,
+    worktree: Option<&Path>,
+    base: &str,
+    branch: &str,
+    strategy: MergeStrategy,
+) -> Result<()> {
+    for name in [base, branch] {
+        if !git::branch_exists(repo, name)? {
+            return Err(Error::MergeRefused(format!("branch '{}' does not exist", name)));
+        }
+    }
+    match git::current_branch(repo)? {
+        Some(current) if current == base => {}
+        current => {
+            return Err(Error::MergeRefused(format!(
+                "{} has {} checked out; check out '{}' there first",
+                repo.display(),
+                current.map_or("a detached HEAD".to_string(), |b| format!("'{}'", b)),
+                base
+            )));
+        }
+    }
+    ensure_clean(repo)?;
+    if let Some(worktree) = worktree {
+        ensure_clean(worktree)?;
+    }
+
+    if git::count_commits(repo, base, branch)? == 0 {
+        return Err(Error::MergeRefused(format!(
+            "'{}' has no commits that are not on '{}'",
+            branch, base
+        )));
+    }
+    let behind = git::count_commits(repo, branch, base)?;
+    if strategy == MergeStrategy::FfOnly && behind > 0 {
+        return Err(Error::MergeRefused(format!(
+            "'{}' has {} commit(s) not on '{}'; not a fast-forward (use --rebase or a merge)",
+            base, behind, branch
+        )));
+    }
+
+    let conflicts = git::merge_conflicts(repo, base, branch)?;
+    if !conflicts.is_empty() {
+        return Err(conflict(branch, base, &conflicts));
+    }
+
+    match strategy {
+        MergeStrategy::Merge => git::merge(repo, branch, false),
+        MergeStrategy::FfOnly => git::merge(repo, branch, true),
+        MergeStrategy::Squash => git::merge_squash(repo, branch),
+        MergeStrategy::Rebase => {
+            if behind > 0 {
+                let worktree = worktree.ok_or_else(|| {
+                    Error::MergeRefused(
+                        "--rebase needs the session's worktree, which no longer exists".to_string(),
+                    )
+                })?;
+                println!("Rebasing '{}' onto '{}'...", branch, base);
+                let conflicts = git::rebase(worktree, base)?;
+                if !conflicts.is_empty() {
+                    return Err(conflict(branch, base, &conflicts));
+                }
+            }
+            git::merge(repo, branch, true)
+        }
+    }
+}
+
+fn ensure_clean(path: &Path) -> Result<()> {
+    let changes = git::uncommitted_changes(path)?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+    Err(Error::DirtyTree(path.to_path_buf(), indent(&changes)))
+}
+
+fn conflict(branch: &str, base: &str, files: &[String]) -> Error {
+    Error::MergeConflict(branch.to_string(), base.to_string(), indent(files))
+}
+
+fn indent(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|l| format!("  {}", l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::{commit, repo_with_worktree, run_git};
+    use std::fs;
+
+    #[test]
+    fn test_strategy_from_flags() {
+        assert_eq!(MergeStrategy::from_flags(false, false, false), MergeStrategy::Merge);
+        assert_eq!(MergeStrategy::from_flags(true, false, false), MergeStrategy::Squash);
+        assert_eq!(MergeStrategy::from_flags(false, true, false), MergeStrategy::Rebase);
+        assert_eq!(MergeStrategy::from_flags(false, false, true), MergeStrategy::FfOnly);
+    }
+
+    #[test]
+    fn test_merge_fast_forwards() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "a.txt", "a\n");
+
+        integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::Merge).unwrap();
+        assert_eq!(
+            run_git(&repo, &["rev-parse", "main"]),
+            run_git(&repo, &["rev-parse", "feature"])
+        );
+        assert!(repo.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_squash_makes_one_commit() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "a.txt", "a\n");
+        commit(&worktree, "b.txt", "b\n");
+
+        integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::Squash).unwrap();
+        assert_eq!(run_git(&repo, &["rev-list", "--count", "main"]), "2");
+        assert!(repo.join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_ff_only_refuses_diverged_base_and_rebase_succeeds() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "a.txt", "a\n");
+        commit(&repo, "b.txt", "b\n");
+
+        let err = integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::FfOnly);
+        assert!(matches!(err, Err(Error::MergeRefused(_))));
+
+        integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::Rebase).unwrap();
+        assert_eq!(run_git(&repo, &["rev-list", "--count", "main"]), "3");
+        assert_eq!(run_git(&repo, &["rev-list", "--merges", "--count", "main"]), "0");
+    }
+
+    #[test]
+    fn test_conflict_is_reported_and_nothing_changes() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "README.md", "from feature\n");
+        commit(&repo, "README.md", "from main\n");
+        let before = run_git(&repo, &["rev-parse", "main"]);
+
+        for strategy in [MergeStrategy::Merge, MergeStrategy::Squash, MergeStrategy::Rebase] {
+            match integrate(&repo, Some(&worktree), "main", "feature", strategy) {
+                Err(Error::MergeConflict(_, _, files)) => assert_eq!(files, "  README.md"),
+                other => panic!("expected a conflict, got {:?}", other),
+            }
+        }
+        assert_eq!(run_git(&repo, &["rev-parse", "main"]), before);
+        assert!(git::uncommitted_changes(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dirty_worktree_is_refused() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "a.txt", "a\n");
+        fs::write(worktree.join("a.txt"), "changed\n").unwrap();
+
+        match integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::Merge) {
+            Err(Error::DirtyTree(path, changes)) => {
+                assert_eq!(path, worktree);
+                assert_eq!(changes, "   M a.txt");
+            }
+            other => panic!("expected a dirty tree, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_base_must_be_checked_out() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        commit(&worktree, "a.txt", "a\n");
+        run_git(&repo, &["branch", "develop"]);
+
+        let err = integrate(&repo, Some(&worktree), "develop", "feature", MergeStrategy::Merge);
+        assert!(matches!(err, Err(Error::MergeRefused(_))));
+    }
+
+    #[test]
+    fn test_nothing_to_merge_is_refused() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+        let err = integrate(&repo, Some(&worktree), "main", "feature", MergeStrategy::Merge);
+        assert!(matches!(err, Err(Error::MergeRefused(_))));
+    }
+}
@@ -1,10 +1,22 @@
+pub mod adopt;
+pub mod batch;
 pub mod cleanup;
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod files;
 pub mod init;
 pub mod kill;
 pub mod list;
 pub mod log_tool;
+pub mod logs;
+pub mod merge;
+pub mod push;
 pub mod new;
+pub mod restart;
 pub mod run;
+pub mod send;
 pub mod status;
 pub mod switch;
 pub mod tools;
+pub mod wait;
@@ -1,12 +1,13 @@
 use crate::cli::ExitKillArg;
+use crate::config;
 use crate::error::{Error, Result};
-use crate::state::{self, Command, Entry, ExitKill};
+use crate::state::{self, Command, Entry, ExitKill, ProcessStatus};
 use crate::{git, tmux};
 use chrono::Utc;
 use serde_json::json;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 pub struct NewArgs {
@@ -17,76 +18,203 @@ pub struct NewArgs {
     pub exit_kill: Option<ExitKillArg>,
     pub remote: String,
     pub base: String,
+    /// Directory worktrees are created in; `None` means next to the repo
+    pub worktree_dir: Option<PathBuf>,
+    /// Worktree path template, resolved against `worktree_dir`
+    pub path_template: String,
+    /// Check out an existing local or remote branch instead of creating one
+    pub existing: bool,
+}
+
+/// Where the worktree's branch comes from.
+#[derive(Debug, PartialEq)]
+pub enum BranchSource {
+    /// Create `branch` from `<remote>/<base>`
+    New { start_point: String },
+    /// Check out the existing local branch
+    Local,
+    /// Create a local branch tracking `<remote>/<branch>`
+    Remote { remote_ref: String },
+}
+
+/// Decide how to get the branch for the worktree. Without `existing`, a local
+/// branch is an error; with it, a local branch wins over the remote one.
+pub fn choose_branch_source(
+    args: &NewArgs,
+    local_exists: bool,
+    remote_exists: bool,
+) -> Result<BranchSource> {
+    if !args.existing {
+        if local_exists {
+            return Err(Error::BranchExists(args.branch.clone()));
+        }
+        return Ok(BranchSource::New {
+            start_point: format!("{}/{}", args.remote, args.base),
+        });
+    }
+
+    if local_exists {
+        Ok(BranchSource::Local)
+    } else if remote_exists {
+        Ok(BranchSource::Remote {
+            remote_ref: format!("{}/{}", args.remote, args.branch),
+        })
+    } else {
+        Err(Error::BranchNotFound(args.branch.clone(), args.remote.clone()))
+    }
 }
 
 pub fn execute(args: NewArgs) -> Result<()> {
-    // Validate command args
+    let ctx = repo_context(&args.remote)?;
+    let plan = plan(&args, &ctx)?;
+
+    // Fetch from remote
+    println!("Fetching from {}...", args.remote);
+    git::fetch(&ctx.repo_path, &args.remote)?;
+
+    create(args, &ctx, plan)
+}
+
+/// The repository sessions are created in, shared by every session of a
+/// `new` or `batch` run.
+pub struct RepoContext {
+    pub current_dir: PathBuf,
+    pub repo_name: String,
+    pub repo_path: PathBuf,
+    pub git_common_dir: PathBuf,
+    /// Derived project prefix
+    pub prefix: String,
+    pub session: String,
+}
+
+/// A session that passed validation, ready to be created once the remote has
+/// been fetched.
+pub struct Plan {
+    pub id: Uuid,
+    pub worktree_path: PathBuf,
+}
+
+fn validate_command(args: &NewArgs) -> Result<()> {
     if args.prompt.is_none() && args.cmd.is_none() {
         return Err(Error::NoCommand);
     }
     if args.prompt.is_some() && args.cmd.is_some() {
         return Err(Error::ConflictingCommands);
     }
+    Ok(())
+}
 
+/// Checks that do not depend on the branch: inside tmux, at the root of a
+/// main repo, and `remote` exists.
+pub fn repo_context(remote: &str) -> Result<RepoContext> {
     // Validate running inside tmux
     if !tmux::is_inside_tmux() {
         return Err(Error::NotInTmux);
     }
 
     // Validate running in git repo
-    if !git::is_git_repo() {
+    let current_dir = env::current_dir()?;
+    if !git::is_git_repo(&current_dir) {
         return Err(Error::NotGitRepo);
     }
 
     // Validate not inside a worktree
-    if git::is_worktree()? {
+    if git::is_worktree(&current_dir)? {
         return Err(Error::InsideWorktree);
     }
 
     // Validate remote exists
-    if !git::remote_exists(&args.remote)? {
-        return Err(Error::RemoteNotFound(args.remote.clone()));
+    if !git::remote_exists(&current_dir, remote)? {
+        return Err(Error::RemoteNotFound(remote.to_string()));
     }
 
     // Derive project prefix
-    let prefix = git::get_project_prefix(&args.remote)?;
+    Ok(RepoContext {
+        repo_name: git::get_repo_name(&current_dir, remote)?,
+        repo_path: git::main_repo_root(&current_dir)?,
+        git_common_dir: git::git_common_dir(&current_dir)?,
+        prefix: git::get_project_prefix(&current_dir, remote)?,
+        session: tmux::get_current_session()?,
+        current_dir,
+    })
+}
+
+/// Per-session checks that can run before anything is created: command
+/// flags, branch and entry not taken, worktree directory free.
+pub fn plan(args: &NewArgs, ctx: &RepoContext) -> Result<Plan> {
+    validate_command(args)?;
 
-    // Check if branch already exists in git
-    if git::branch_exists(&args.branch)? {
+    // Check if branch already exists in git (only an error without --existing)
+    if !args.existing && git::branch_exists(&ctx.repo_path, &args.branch)? {
         return Err(Error::BranchExists(args.branch.clone()));
     }
 
     // Check if entry already exists in state
-    if state::find_by_branch(&args.branch)?.is_some() {
-        return Err(Error::EntryExists(args.branch.clone()));
+    if state::find_in_repo(&ctx.git_common_dir, &ctx.prefix, &args.branch)?.is_some() {
+        return Err(Error::EntryExists(format!("{}:{}", ctx.prefix, args.branch)));
     }
 
     // Calculate worktree path
-    let current_dir = env::current_dir()?;
-    let parent = current_dir
-        .parent()
-        .ok_or_else(|| Error::Git("Cannot get parent directory".to_string()))?;
-    let worktree_path = parent.join(format!("{}-{}", prefix, args.branch));
+    let id = Uuid::new_v4();
+    let parent = match args.worktree_dir.clone() {
+        Some(dir) => dir,
+        None => ctx
+            .current_dir
+            .parent()
+            .ok_or_else(|| Error::Git("Cannot get parent directory".to_string()))?
+            .to_path_buf(),
+    };
+    let relative = render_path_template(
+        &args.path_template,
+        &TemplateVars {
+            project: &ctx.prefix,
+            repo: &ctx.repo_name,
+            branch: &args.branch,
+            date: &Utc::now().format("%Y-%m-%d").to_string(),
+            short_id: &id.simple().to_string()[..8],
+        },
+    )?;
+    let worktree_path = parent.join(relative);
 
     // Check if directory already exists
     if worktree_path.exists() {
         return Err(Error::DirectoryExists(worktree_path));
     }
 
-    // Fetch from remote
-    println!("Fetching from {}...", args.remote);
-    git::fetch(&args.remote)?;
+    Ok(Plan { id, worktree_path })
+}
+
+/// Create the worktree, state entry and tmux window of a planned session.
+/// The remote must have been fetched.
+pub fn create(args: NewArgs, ctx: &RepoContext, plan: Plan) -> Result<()> {
+    let Plan { id, worktree_path } = plan;
+    let repo_path = &ctx.repo_path;
 
     // Create worktree
-    let start_point = format!("{}/{}", args.remote, args.base);
+    let source = choose_branch_source(
+        &args,
+        git::branch_exists(repo_path, &args.branch)?,
+        git::remote_branch_exists(repo_path, &args.remote, &args.branch)?,
+    )?;
     println!("Creating worktree at {:?}...", worktree_path);
-    git::add_worktree(&worktree_path, &args.branch, &start_point)?;
+    match &source {
+        BranchSource::New { start_point } => {
+            git::add_worktree(repo_path, &worktree_path, &args.branch, start_point)?
+        }
+        BranchSource::Local => {
+            println!("Checking out existing branch '{}'...", args.branch);
+            git::add_worktree_existing(repo_path, &worktree_path, &args.branch)?
+        }
+        BranchSource::Remote { remote_ref } => {
+            println!("Tracking '{}'...", remote_ref);
+            git::add_worktree_tracking(repo_path, &worktree_path, &args.branch, remote_ref)?
+        }
+    }
 
     // Get wortex binary path (needed for hooks config)
     let wortex_bin = env::current_exe()?;
 
-    // Get tmux session
-    let session = tmux::get_current_session()?;
+    let session = &ctx.session;
 
     // Create state entry
     let command = if let Some(prompt) = args.prompt {
@@ -106,17 +234,38 @@ pub fn execute(args: NewArgs) -> Result<()> {
         ExitKillArg::Codes(codes) => ExitKill::Codes(codes),
     });
 
+    // The same branch may already have a window from another repo
+    let window = if tmux::window_exists(session, &args.branch)? {
+        format!("{}/{}", ctx.prefix, args.branch)
+    } else {
+        args.branch.clone()
+    };
+
+    let now = Utc::now();
     let entry = Entry {
-        id: Uuid::new_v4(),
-        project: prefix,
+        id,
+        project: ctx.prefix.clone(),
         branch: args.branch.clone(),
         path: worktree_path.clone(),
         tmux_session: session.clone(),
-        tmux_window: args.branch.clone(),
+        tmux_window: window.clone(),
         command,
         exit_kill,
         exit_code: None,
-        created_at: Utc::now(),
+        status: ProcessStatus::Spawned,
+        pid: None,
+        blocked_on: None,
+        repo_path: Some(repo_path.clone()),
+        git_common_dir: Some(ctx.git_common_dir.clone()),
+        remote: Some(args.remote.clone()),
+        base: Some(args.base.clone()),
+        created_branch: matches!(source, BranchSource::New { .. }),
+        removed_at: None,
+        run_count: 0,
+        pushed_sha: None,
+        pushed_at: None,
+        created_at: now,
+        updated_at: now,
     };
 
     // Save entry before creating window
@@ -130,8 +279,12 @@ pub fn execute(args: NewArgs) -> Result<()> {
 
     // Create tmux window with wortex __run command
     let run_command = format!("{} __run {}", wortex_bin.display(), entry.id);
-    println!("Creating tmux window '{}'...", args.branch);
-    tmux::create_window(&session, &args.branch, &worktree_path, &run_command)?;
+    let log_path = state::log_path(entry.id)?;
+    if let Some(dir) = log_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    println!("Creating tmux window '{}'...", window);
+    tmux::create_window(session, &window, &worktree_path, &run_command, &log_path)?;
 
     println!(
         "Created worktree and tmux window for branch '{}'",
@@ -140,7 +293,78 @@ pub fn execute(args: NewArgs) -> Result<()> {
     Ok(())
 }
 
-/// Creates .claude/settings.local.json with hooks to log tool usage
+pub struct TemplateVars<'a> {
+    pub project: &'a str,
+    pub repo: &'a str,
+    pub branch: &'a str,
+    pub date: &'a str,
+    pub short_id: &'a str,
+}
+
+/// Expand a worktree path template.
+///
+/// Placeholders: `{project}` (derived prefix), `{repo}` (repo name),
+/// `{branch_slug}` (branch made safe for a single path component),
+/// `{date}` (YYYY-MM-DD) and `{short_id}` (first 8 hex chars of the entry id).
+/// A leading `~` is expanded to the home directory.
+pub fn render_path_template(template: &str, vars: &TemplateVars) -> Result<PathBuf> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| Error::Config(format!("unclosed '{{' in path template '{}'", template)))?;
+        let value = match &after[..end] {
+            "project" => vars.project.to_string(),
+            "repo" => vars.repo.to_string(),
+            "branch_slug" => slugify(vars.branch),
+            "date" => vars.date.to_string(),
+            "short_id" => vars.short_id.to_string(),
+            other => {
+                return Err(Error::Config(format!(
+                    "unknown placeholder '{{{}}}' in path template '{}'",
+                    other, template
+                )))
+            }
+        };
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    Ok(config::expand_tilde(&out))
+}
+
+/// Make a branch name safe to use as a single path component:
+/// anything other than ASCII alphanumerics, `.`, `_` and `-` becomes `-`,
+/// runs of `-` collapse, and leading/trailing `-`/`.` are trimmed.
+pub fn slugify(branch: &str) -> String {
+    let mut slug = String::with_capacity(branch.len());
+    for c in branch.chars() {
+        let c = if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+            c
+        } else {
+            '-'
+        };
+        if c == '-' && slug.ends_with('-') {
+            continue;
+        }
+        slug.push(c);
+    }
+
+    let slug = slug.trim_matches(|c| c == '-' || c == '.');
+    if slug.is_empty() {
+        "branch".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Creates .claude/settings.local.json with hooks to log tool usage and to
+/// track when the agent is waiting on the user
 fn create_claude_hooks_config(
     worktree_path: &Path,
     wortex_bin: &Path,
@@ -175,6 +399,17 @@ fn create_claude_hooks_config(
                         }
                     ]
                 }
+            ],
+            "Notification": [
+                {
+                    "matcher": ".*",
+                    "hooks": [
+                        {
+                            "type": "command",
+                            "command": format!("{} __log-tool {} notification", wortex_path, session_str)
+                        }
+                    ]
+                }
             ]
         }
     });
@@ -189,9 +424,132 @@ fn create_claude_hooks_config(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
     use tempfile::TempDir;
 
+    fn new_args(existing: bool) -> NewArgs {
+        NewArgs {
+            branch: "fix-ci".to_string(),
+            prompt: None,
+            cmd: Some("true".to_string()),
+            agent: None,
+            exit_kill: None,
+            remote: "origin".to_string(),
+            base: "main".to_string(),
+            worktree_dir: None,
+            path_template: "{project}-{branch_slug}".to_string(),
+            existing,
+        }
+    }
+
+    #[test]
+    fn test_branch_source_new_branch() {
+        let source = choose_branch_source(&new_args(false), false, true).unwrap();
+        assert_eq!(
+            source,
+            BranchSource::New {
+                start_point: "origin/main".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_branch_source_refuses_existing_local_without_flag() {
+        let result = choose_branch_source(&new_args(false), true, false);
+        assert!(matches!(result, Err(Error::BranchExists(_))));
+    }
+
+    #[test]
+    fn test_branch_source_existing_prefers_local() {
+        let source = choose_branch_source(&new_args(true), true, true).unwrap();
+        assert_eq!(source, BranchSource::Local);
+    }
+
+    #[test]
+    fn test_branch_source_existing_tracks_remote() {
+        let source = choose_branch_source(&new_args(true), false, true).unwrap();
+        assert_eq!(
+            source,
+            BranchSource::Remote {
+                remote_ref: "origin/fix-ci".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_branch_source_existing_missing_everywhere() {
+        let result = choose_branch_source(&new_args(true), false, false);
+        assert!(matches!(result, Err(Error::BranchNotFound(_, _))));
+    }
+
+    fn vars(branch: &str) -> TemplateVars<'_> {
+        TemplateVars {
+            project: "mp",
+            repo: "my-project",
+            branch,
+            date: "2025-01-31",
+            short_id: "550e8400",
+        }
+    }
+
+    #[test]
+    fn test_slugify_replaces_slashes() {
+        assert_eq!(slugify("feature/login"), "feature-login");
+    }
+
+    #[test]
+    fn test_slugify_collapses_and_trims() {
+        assert_eq!(slugify("fix//weird  name"), "fix-weird-name");
+        assert_eq!(slugify("/leading/"), "leading");
+        assert_eq!(slugify(".hidden"), "hidden");
+        assert_eq!(slugify("a..b"), "a..b");
+    }
+
+    #[test]
+    fn test_slugify_non_ascii_and_empty() {
+        assert_eq!(slugify("café"), "caf");
+        assert_eq!(slugify("///"), "branch");
+    }
+
+    #[test]
+    fn test_render_default_template() {
+        let path = render_path_template("{project}-{branch_slug}", &vars("feature/login")).unwrap();
+        assert_eq!(path, PathBuf::from("mp-feature-login"));
+    }
+
+    #[test]
+    fn test_render_all_placeholders() {
+        let path = render_path_template(
+            "/wt/{repo}/{date}-{short_id}-{branch_slug}",
+            &vars("fix-ci"),
+        )
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/wt/my-project/2025-01-31-550e8400-fix-ci"));
+    }
+
+    #[test]
+    fn test_render_expands_tilde() {
+        let path = render_path_template("~/worktrees/{project}/{branch_slug}", &vars("a/b")).unwrap();
+        assert_eq!(path, dirs::home_dir().unwrap().join("worktrees/mp/a-b"));
+    }
+
+    #[test]
+    fn test_render_absolute_template_ignores_base_dir() {
+        let path = render_path_template("/abs/{branch_slug}", &vars("x")).unwrap();
+        assert_eq!(PathBuf::from("/repos").join(path), PathBuf::from("/abs/x"));
+    }
+
+    #[test]
+    fn test_render_rejects_unknown_placeholder() {
+        assert!(matches!(
+            render_path_template("{nope}", &vars("x")),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(
+            render_path_template("{project", &vars("x")),
+            Err(Error::Config(_))
+        ));
+    }
+
     #[test]
     fn test_create_hooks_config_creates_directory_and_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -253,6 +611,25 @@ mod tests {
         assert!(content.contains("/custom/path/to/wortex"));
     }
 
+    #[test]
+    fn test_create_hooks_config_contains_notification_hook() {
+        let temp_dir = TempDir::new().unwrap();
+        let worktree_path = temp_dir.path();
+        let wortex_bin = PathBuf::from("/usr/bin/wortex");
+        let session_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+
+        create_claude_hooks_config(worktree_path, &wortex_bin, session_id).unwrap();
+
+        let settings_path = worktree_path.join(".claude").join("settings.local.json");
+        let content = fs::read_to_string(&settings_path).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+
+        assert_eq!(
+            settings["hooks"]["Notification"][0]["hooks"][0]["command"],
+            "/usr/bin/wortex __log-tool 550e8400-e29b-41d4-a716-446655440000 notification"
+        );
+    }
+
     #[test]
     fn test_create_hooks_config_matcher_is_wildcard() {
         let temp_dir = TempDir::new().unwrap();
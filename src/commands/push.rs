@@ -0,0 +1,161 @@
+use crate::error::Result;
+use crate::git;
+use crate::state::{self, Entry, ProjectScope};
+use std::path::Path;
+
+pub struct PushArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    /// Used when the entry has no recorded remote (from config)
+    pub remote: String,
+    pub force_with_lease: bool,
+}
+
+/// How a session branch compares with what `wortex push` last pushed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PushState {
+    NotPushed,
+    /// The branch is at the pushed commit
+    Pushed,
+    /// The branch has commits on top of the pushed one
+    Ahead(usize),
+    /// The branch was rewritten since the push (needs --force-with-lease)
+    Diverged,
+}
+
+impl PushState {
+    pub fn label(&self) -> String {
+        match self {
+            PushState::NotPushed => "-".to_string(),
+            PushState::Pushed => "pushed".to_string(),
+            PushState::Ahead(n) => format!("ahead {}", n),
+            PushState::Diverged => "diverged".to_string(),
+        }
+    }
+}
+
+pub fn execute(args: PushArgs) -> Result<()> {
+    let entry = state::resolve(&args.branch, &args.scope)?;
+    let repo = entry.repo_dir()?;
+    let remote = entry.remote.clone().unwrap_or(args.remote);
+
+    println!("Pushing '{}' to {}...", entry.branch, remote);
+    git::push(&repo, &remote, &entry.branch, args.force_with_lease)?;
+
+    // The remote-tracking ref is what the remote now has
+    let sha = git::rev_parse(&repo, &format!("refs/remotes/{}/{}", remote, entry.branch))
+        .or_else(|_| git::rev_parse(&repo, &entry.branch))?;
+    state::mark_pushed(entry.id, &remote, &sha)?;
+
+    println!(
+        "Pushed '{}' to {}/{} ({})",
+        entry.branch,
+        remote,
+        entry.branch,
+        &sha[..sha.len().min(8)]
+    );
+    Ok(())
+}
+
+/// Compare the entry's branch with its last pushed commit. A branch (or
+/// repo) that can no longer be resolved counts as not pushed.
+pub fn push_state(entry: &Entry) -> PushState {
+    match (&entry.pushed_sha, entry.repo_dir()) {
+        (Some(pushed), Ok(repo)) => compare(&repo, &entry.branch, pushed),
+        _ => PushState::NotPushed,
+    }
+}
+
+fn compare(repo: &Path, branch: &str, pushed: &str) -> PushState {
+    let Ok(head) = git::rev_parse(repo, branch) else {
+        return PushState::NotPushed;
+    };
+    if head == pushed {
+        return PushState::Pushed;
+    }
+    match (
+        git::count_commits(repo, pushed, branch),
+        git::count_commits(repo, branch, pushed),
+    ) {
+        (Ok(ahead), Ok(0)) => PushState::Ahead(ahead),
+        _ => PushState::Diverged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::{commit, init_repo, run_git};
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// A repo on branch `feature` whose `origin` is a local bare repository.
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let remote = temp_dir.path().join("remote.git");
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir(&remote).unwrap();
+        run_git(&remote, &["init", "-q", "--bare"]);
+        init_repo(&repo, "feature");
+        run_git(&repo, &["remote", "add", "origin", remote.to_str().unwrap()]);
+        (temp_dir, repo, remote)
+    }
+
+    #[test]
+    fn test_push_sets_upstream() {
+        let (_tmp, repo, remote) = setup();
+        git::push(&repo, "origin", "feature", false).unwrap();
+
+        assert_eq!(
+            run_git(&remote, &["rev-parse", "feature"]),
+            run_git(&repo, &["rev-parse", "feature"])
+        );
+        assert_eq!(
+            run_git(&repo, &["rev-parse", "--abbrev-ref", "feature@{upstream}"]),
+            "origin/feature"
+        );
+    }
+
+    #[test]
+    fn test_push_state() {
+        let (_tmp, repo, _remote) = setup();
+        commit(&repo, "x.txt", "x\n");
+        git::push(&repo, "origin", "feature", false).unwrap();
+        let pushed = git::rev_parse(&repo, "origin/feature").unwrap();
+        assert_eq!(compare(&repo, "feature", &pushed), PushState::Pushed);
+
+        commit(&repo, "a.txt", "a\n");
+        commit(&repo, "b.txt", "b\n");
+        assert_eq!(compare(&repo, "feature", &pushed), PushState::Ahead(2));
+
+        run_git(&repo, &["reset", "-q", "--hard", &format!("{}~1", pushed)]);
+        run_git(&repo, &["commit", "-q", "--allow-empty", "-m", "rewritten"]);
+        assert_eq!(compare(&repo, "feature", &pushed), PushState::Diverged);
+
+        assert_eq!(compare(&repo, "gone", &pushed), PushState::NotPushed);
+    }
+
+    #[test]
+    fn test_rewritten_branch_needs_force_with_lease() {
+        let (_tmp, repo, _remote) = setup();
+        commit(&repo, "a.txt", "a\n");
+        git::push(&repo, "origin", "feature", false).unwrap();
+
+        run_git(&repo, &["commit", "-q", "--amend", "-m", "amended"]);
+        assert!(git::push(&repo, "origin", "feature", false).is_err());
+        git::push(&repo, "origin", "feature", true).unwrap();
+        assert_eq!(
+            git::rev_parse(&repo, "origin/feature").unwrap(),
+            git::rev_parse(&repo, "feature").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_push_state_labels() {
+        assert_eq!(PushState::NotPushed.label(), "-");
+        assert_eq!(PushState::Pushed.label(), "pushed");
+        assert_eq!(PushState::Ahead(3).label(), "ahead 3");
+        assert_eq!(PushState::Diverged.label(), "diverged");
+    }
+}
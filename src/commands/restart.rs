@@ -0,0 +1,114 @@
+use crate::error::{Error, Result};
+use crate::state::{self, Command, Entry, ProjectScope};
+use crate::tmux;
+use chrono::Utc;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+pub struct RestartArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    /// Restart even if the command is still running (it gets killed)
+    pub force: bool,
+}
+
+pub fn execute(args: RestartArgs) -> Result<()> {
+    let entry = state::resolve(&args.branch, &args.scope)?;
+
+    if matches!(entry.command, Command::External) {
+        return Err(Error::ExternalEntry(entry.branch.clone()));
+    }
+    if !entry.path.exists() {
+        return Err(Error::WorktreeMissing(entry.path.clone()));
+    }
+    if !args.force && is_running(&entry, state::pid_alive) {
+        return Err(Error::StillRunning(entry.branch.clone()));
+    }
+
+    let run_number = entry.run_count + 1;
+    state::reset_for_restart(entry.id)?;
+
+    // Mark where the new attempt starts in the log
+    let log_path = state::log_path(entry.id)?;
+    if let Some(dir) = log_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+    writeln!(
+        log,
+        "\n--- wortex restart: run #{} at {} ---",
+        run_number,
+        Utc::now().format("%Y-%m-%d %H:%M:%S")
+    )?;
+
+    let run_command = format!("{} __run {}", env::current_exe()?.display(), entry.id);
+    if tmux::window_exists(&entry.tmux_session, &entry.tmux_window)? {
+        println!("Respawning tmux window '{}'...", entry.tmux_window);
+        tmux::respawn_logged(
+            &entry.tmux_session,
+            &entry.tmux_window,
+            &entry.path,
+            &run_command,
+            &log_path,
+        )?;
+    } else {
+        println!("Creating tmux window '{}'...", entry.tmux_window);
+        tmux::create_window(
+            &entry.tmux_session,
+            &entry.tmux_window,
+            &entry.path,
+            &run_command,
+            &log_path,
+        )?;
+    }
+
+    println!("Restarted '{}' (run #{})", entry.branch, run_number);
+    Ok(())
+}
+
+/// Whether the entry's command is still alive. A recorded `running` or
+/// `waiting` status whose PID is gone (supervisor died) counts as finished.
+fn is_running<F>(entry: &Entry, pid_alive: F) -> bool
+where
+    F: Fn(u32) -> bool,
+{
+    entry.status.is_live() && entry.pid.is_some_and(pid_alive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ProcessStatus;
+    use std::path::PathBuf;
+
+    fn make_entry(status: ProcessStatus, pid: Option<u32>) -> Entry {
+        Entry {
+            path: PathBuf::from("/nonexistent/tp-feat"),
+            status,
+            pid,
+            run_count: 1,
+            ..Entry::for_test("tp", "feat")
+        }
+    }
+
+    #[test]
+    fn test_is_running_live_pid() {
+        let entry = make_entry(ProcessStatus::Running, Some(42));
+        assert!(is_running(&entry, |_| true));
+    }
+
+    #[test]
+    fn test_is_running_lost_pid() {
+        let entry = make_entry(ProcessStatus::Running, Some(42));
+        assert!(!is_running(&entry, |_| false));
+    }
+
+    #[test]
+    fn test_is_running_finished() {
+        for status in [ProcessStatus::Exited, ProcessStatus::Killed, ProcessStatus::Signaled] {
+            let entry = make_entry(status, Some(42));
+            assert!(!is_running(&entry, |_| true));
+        }
+    }
+}
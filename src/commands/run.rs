@@ -1,7 +1,10 @@
+use crate::config;
 use crate::error::{Error, Result};
-use crate::state::{self, Command};
+use crate::notify;
+use crate::state::{self, Command, Entry, ProcessStatus};
 use crate::tmux;
-use std::process::{Command as ProcessCommand, Stdio};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command as ProcessCommand, ExitStatus, Stdio};
 use uuid::Uuid;
 
 pub fn execute(id: &str) -> Result<()> {
@@ -21,24 +24,35 @@ pub fn execute(id: &str) -> Result<()> {
                 args.insert(0, "--agent".to_string());
                 args.insert(1, agent.clone());
             }
-            ("claude".to_string(), args)
+            // A broken config file must not keep the session from starting
+            let config = config::load(&entry.path).unwrap_or_else(|e| {
+                eprintln!("Warning: {}; using the default configuration", e);
+                config::Config::default()
+            });
+            (config.agent_binary().to_string(), args)
         }
         Command::Raw { cmd } => {
             // Run via shell
             ("sh".to_string(), vec!["-c".to_string(), cmd.clone()])
         }
+        Command::External => return Err(Error::ExternalEntry(entry.branch.clone())),
     };
 
-    // Execute the command
-    let status = ProcessCommand::new(&program)
+    // Spawn the command and record its PID
+    let mut child = ProcessCommand::new(&program)
         .args(&args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .current_dir(&entry.path)
-        .status()?;
+        .spawn()?;
 
-    let exit_code = status.code().unwrap_or(1);
+    // The child is already running: losing the PID must not orphan it
+    if let Err(e) = state::mark_running(entry.id, child.id()) {
+        eprintln!("Warning: could not record PID {}: {}", child.id(), e);
+    }
+
+    let (final_status, exit_code) = classify_exit(child.wait()?);
 
     // Check if we should kill the window
     let should_kill = entry
@@ -47,16 +61,61 @@ pub fn execute(id: &str) -> Result<()> {
         .map(|ek| ek.matches(exit_code))
         .unwrap_or(false);
 
+    // Record the final status first so an archived entry still shows it
+    state::mark_terminated(entry.id, final_status, exit_code)?;
+
+    // Notify before an exit-kill takes the window away
+    let finished = Entry {
+        status: final_status,
+        exit_code: Some(exit_code),
+        ..entry.clone()
+    };
+    notify::emit(notify::Event::for_exit(final_status, exit_code), &finished, None);
+
     if should_kill {
-        // Remove entry from state
-        state::remove_entry(entry.id)?;
+        // Archive the entry (its log stays readable)
+        state::archive_entry(entry.id)?;
 
         // Kill own tmux window
         let _ = tmux::kill_window(&entry.tmux_session, &entry.tmux_window);
-    } else {
-        // Update state with exit code
-        state::update_exit_code(entry.id, exit_code)?;
     }
 
     std::process::exit(exit_code);
 }
+
+/// Map a child's exit status to the recorded process status and exit code.
+/// Signal terminations use the shell convention of 128 + signal number.
+fn classify_exit(status: ExitStatus) -> (ProcessStatus, i32) {
+    if let Some(code) = status.code() {
+        return (ProcessStatus::Exited, code);
+    }
+    match status.signal() {
+        Some(9) => (ProcessStatus::Killed, 128 + 9),
+        Some(sig) => (ProcessStatus::Signaled, 128 + sig),
+        None => (ProcessStatus::Exited, 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_normal_exit() {
+        // Raw wait status: exit code lives in the high byte
+        let status = ExitStatus::from_raw(3 << 8);
+        assert_eq!(classify_exit(status), (ProcessStatus::Exited, 3));
+    }
+
+    #[test]
+    fn test_classify_sigkill_is_killed() {
+        let status = ExitStatus::from_raw(9);
+        assert_eq!(classify_exit(status), (ProcessStatus::Killed, 137));
+    }
+
+    #[test]
+    fn test_classify_other_signal_is_signaled() {
+        let status = ExitStatus::from_raw(15);
+        assert_eq!(classify_exit(status), (ProcessStatus::Signaled, 143));
+    }
+}
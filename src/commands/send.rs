@@ -0,0 +1,58 @@
+use crate::error::{Error, Result};
+use crate::state::{self, ProjectScope};
+use crate::tmux;
+
+/// Event kind recorded for each message sent to a pane.
+pub const EVENT_INPUT: &str = "input";
+
+pub struct SendArgs {
+    pub branch: String,
+    pub scope: ProjectScope,
+    pub text: String,
+    /// Press Enter after the text
+    pub enter: bool,
+}
+
+pub fn execute(args: SendArgs) -> Result<()> {
+    let entry = state::resolve(&args.branch, &args.scope)?;
+
+    if !tmux::window_exists(&entry.tmux_session, &entry.tmux_window)? {
+        return Err(Error::WindowNotFound(entry.tmux_window.clone()));
+    }
+    if tmux::pane_dead(&entry.tmux_session, &entry.tmux_window)? {
+        return Err(Error::PaneDead(entry.branch.clone()));
+    }
+
+    tmux::send_text(&entry.tmux_session, &entry.tmux_window, &args.text, args.enter)?;
+    state::record_event(entry.id, EVENT_INPUT, Some(&event_detail(&args.text, args.enter)))?;
+
+    println!(
+        "Sent {} line(s) to '{}'{}",
+        args.text.lines().count().max(1),
+        entry.branch,
+        if args.enter { " and pressed Enter" } else { "" }
+    );
+    Ok(())
+}
+
+/// What is stored for a sent message: the text, with a trailing newline when
+/// Enter was pressed (as the pane received it).
+fn event_detail(text: &str, enter: bool) -> String {
+    if enter {
+        format!("{}\n", text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_detail() {
+        assert_eq!(event_detail("also add tests", false), "also add tests");
+        assert_eq!(event_detail("also add tests", true), "also add tests\n");
+        assert_eq!(event_detail("a\nb", true), "a\nb\n");
+    }
+}
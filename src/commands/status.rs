@@ -1,34 +1,263 @@
 use crate::error::Result;
 use crate::git;
-use crate::state;
+use crate::state::{self, Entry};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::thread;
 
-pub fn execute() -> Result<()> {
+pub struct StatusArgs {
+    pub json: bool,
+    /// Used for entries with no recorded remote or base (from config)
+    pub remote: String,
+    pub base: String,
+}
+
+/// Git state of one tracked worktree.
+#[derive(Debug, Default, Serialize)]
+struct WorktreeStatus {
+    project: String,
+    branch: String,
+    path: PathBuf,
+    exists: bool,
+    /// `<remote>/<base>` the branch is compared with
+    base: String,
+    ahead_base: Option<usize>,
+    behind_base: Option<usize>,
+    upstream: Option<String>,
+    ahead_upstream: Option<usize>,
+    behind_upstream: Option<usize>,
+    staged: usize,
+    unstaged: usize,
+    untracked: usize,
+    conflicted: usize,
+    /// "merging" or "rebasing"
+    operation: Option<&'static str>,
+    last_commit: Option<String>,
+    last_commit_at: Option<DateTime<Utc>>,
+    /// Set when a git query failed; the other fields may be incomplete
+    error: Option<String>,
+}
+
+impl WorktreeStatus {
+    fn state(&self) -> &'static str {
+        if !self.exists {
+            "missing"
+        } else if self.error.is_some() {
+            "error"
+        } else if let Some(operation) = self.operation {
+            operation
+        } else if self.staged + self.unstaged + self.untracked + self.conflicted == 0 {
+            "clean"
+        } else {
+            "dirty"
+        }
+    }
+}
+
+pub fn execute(args: StatusArgs) -> Result<()> {
     let state = state::load()?;
 
     if state.entries.is_empty() {
-        println!("No tracked worktrees.");
+        if args.json {
+            println!("[]");
+        } else {
+            println!("No tracked worktrees.");
+        }
         return Ok(());
     }
 
-    for entry in &state.entries {
-        println!("=== {} ===", entry.branch);
+    // Each worktree needs several git calls; query them all at once
+    let statuses: Vec<WorktreeStatus> = thread::scope(|scope| {
+        let handles: Vec<_> = state
+            .entries
+            .iter()
+            .map(|entry| scope.spawn(|| collect(entry, &args.remote, &args.base)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("status thread panicked"))
+            .collect()
+    });
 
-        if !entry.path.exists() {
-            println!("  (worktree not found)");
-            println!();
-            continue;
-        }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
 
-        let status = git::status_short(&entry.path)?;
-        if status.trim().is_empty() {
-            println!("  (clean)");
-        } else {
-            for line in status.lines() {
-                println!("  {}", line);
-            }
+    let now = Utc::now();
+    println!(
+        "{:<8} {:<20} {:<9} {:<9} {:>6} {:>8} {:>9} {:<9} LAST COMMIT",
+        "PROJECT", "BRANCH", "BASE", "UPSTREAM", "STAGED", "UNSTAGED", "UNTRACKED", "STATE"
+    );
+    for status in &statuses {
+        let last_commit = match (&status.last_commit, status.last_commit_at) {
+            (Some(subject), Some(at)) => format!(
+                "{} ({} ago)",
+                truncate(subject, 40),
+                format_age((now - at).num_seconds())
+            ),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<8} {:<20} {:<9} {:<9} {:>6} {:>8} {:>9} {:<9} {}",
+            status.project,
+            status.branch,
+            ahead_behind(status.ahead_base, status.behind_base),
+            ahead_behind(status.ahead_upstream, status.behind_upstream),
+            status.staged,
+            status.unstaged,
+            status.untracked,
+            status.state(),
+            last_commit
+        );
+        if let Some(error) = &status.error {
+            println!("{:<8} error: {}", "", error.trim());
         }
-        println!();
     }
+    println!();
+    println!("BASE and UPSTREAM show commits ahead (+) and behind (-).");
 
     Ok(())
 }
+
+fn collect(entry: &Entry, remote: &str, base: &str) -> WorktreeStatus {
+    let mut status = WorktreeStatus {
+        project: entry.project.clone(),
+        branch: entry.branch.clone(),
+        path: entry.path.clone(),
+        exists: entry.path.exists(),
+        base: format!(
+            "{}/{}",
+            entry.remote.as_deref().unwrap_or(remote),
+            entry.base.as_deref().unwrap_or(base)
+        ),
+        ..Default::default()
+    };
+    if status.exists
+        && let Err(e) = fill(&mut status)
+    {
+        status.error = Some(e.to_string());
+    }
+    status
+}
+
+fn fill(status: &mut WorktreeStatus) -> Result<()> {
+    let path = &status.path;
+
+    let summary = git::status_summary(path)?;
+    status.staged = summary.staged;
+    status.unstaged = summary.unstaged;
+    status.untracked = summary.untracked;
+    status.conflicted = summary.conflicted;
+    status.upstream = summary.upstream;
+    if let Some((ahead, behind)) = summary.upstream_ahead_behind {
+        status.ahead_upstream = Some(ahead);
+        status.behind_upstream = Some(behind);
+    }
+
+    status.operation = git::operation_in_progress(path)?;
+
+    if let Some((subject, time)) = git::last_commit(path)? {
+        status.last_commit = Some(subject);
+        status.last_commit_at = DateTime::from_timestamp(time, 0);
+    }
+
+    let (ahead, behind) = git::ahead_behind(path, &status.base, "HEAD")?;
+    status.ahead_base = Some(ahead);
+    status.behind_base = Some(behind);
+    Ok(())
+}
+
+fn ahead_behind(ahead: Option<usize>, behind: Option<usize>) -> String {
+    match (ahead, behind) {
+        (Some(ahead), Some(behind)) => format!("+{} -{}", ahead, behind),
+        _ => "-".to_string(),
+    }
+}
+
+/// Coarse age: "45s", "12m", "3h", "5d".
+fn format_age(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if seconds < 3600 {
+        format!("{}m", seconds / 60)
+    } else if seconds < 86_400 {
+        format!("{}h", seconds / 3600)
+    } else {
+        format!("{}d", seconds / 86_400)
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() > max_chars {
+        let truncated: String = s.chars().take(max_chars - 3).collect();
+        format!("{}...", truncated)
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::{commit, repo_with_worktree, try_git};
+    use std::fs;
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(-5), "0s");
+        assert_eq!(format_age(45), "45s");
+        assert_eq!(format_age(12 * 60 + 30), "12m");
+        assert_eq!(format_age(3 * 3600), "3h");
+        assert_eq!(format_age(5 * 86_400 + 10), "5d");
+    }
+
+    #[test]
+    fn test_ahead_behind_and_truncate() {
+        assert_eq!(ahead_behind(Some(2), Some(0)), "+2 -0");
+        assert_eq!(ahead_behind(None, None), "-");
+        assert_eq!(truncate("short", 40), "short");
+        assert_eq!(truncate("abcdefghij", 8), "abcde...");
+    }
+
+    #[test]
+    fn test_collect() {
+        let (_tmp, repo, worktree) = repo_with_worktree();
+
+        commit(&repo, "base.txt", "base\n");
+        commit(&worktree, "README.md", "feature\n");
+        commit(&worktree, "a.txt", "a\n");
+        fs::write(worktree.join("a.txt"), "changed\n").unwrap();
+        fs::write(worktree.join("new.txt"), "new\n").unwrap();
+
+        let mut status = WorktreeStatus {
+            path: worktree.clone(),
+            exists: true,
+            base: "main".to_string(),
+            ..Default::default()
+        };
+        fill(&mut status).unwrap();
+        assert_eq!((status.ahead_base, status.behind_base), (Some(2), Some(1)));
+        assert_eq!((status.staged, status.unstaged, status.untracked), (0, 1, 1));
+        assert_eq!(status.upstream, None);
+        assert_eq!(status.last_commit.as_deref(), Some("edit a.txt"));
+        assert_eq!(status.state(), "dirty");
+
+        // A conflicting merge in progress
+        fs::write(worktree.join("a.txt"), "a\n").unwrap();
+        commit(&repo, "README.md", "main\n");
+        assert!(!try_git(&worktree, &["merge", "main"]));
+        let mut status = WorktreeStatus {
+            path: worktree.clone(),
+            exists: true,
+            base: "main".to_string(),
+            ..Default::default()
+        };
+        fill(&mut status).unwrap();
+        assert_eq!(status.operation, Some("merging"));
+        assert_eq!(status.conflicted, 1);
+        assert_eq!(status.state(), "merging");
+    }
+}
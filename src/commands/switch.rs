@@ -1,15 +1,14 @@
 use crate::error::{Error, Result};
-use crate::state;
+use crate::state::{self, ProjectScope};
 use crate::tmux;
 
-pub fn execute(branch: &str) -> Result<()> {
+pub fn execute(branch: &str, scope: &ProjectScope) -> Result<()> {
     // Find the entry
-    let entry = state::find_by_branch(branch)?
-        .ok_or_else(|| Error::EntryNotFound(branch.to_string()))?;
+    let entry = state::resolve(branch, scope)?;
 
     // Check if window exists
     if !tmux::window_exists(&entry.tmux_session, &entry.tmux_window)? {
-        return Err(Error::WindowNotFound(branch.to_string()));
+        return Err(Error::WindowNotFound(entry.tmux_window.clone()));
     }
 
     // Switch to the window
@@ -1,33 +1,62 @@
-use crate::db::{self, ToolCall};
+use crate::db::{self, ToolCall, ToolCallFilter, ToolStats};
 use crate::error::Result;
-use crate::state;
+use crate::commands::wait::{self, Outcome};
+use crate::state::{self, ProcessStatus, ProjectScope};
+use crate::tmux;
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::thread;
+use std::time::Duration;
+
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct ToolsArgs {
     pub branch: Option<String>,
+    pub scope: ProjectScope,
     pub json: bool,
     pub hook_type: Option<String>,
+    /// Most recent calls to show
     pub limit: Option<usize>,
+    /// Only these tools (any of them)
+    pub tools: Vec<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// `--project` as given; without a branch it filters calls by project
+    pub project: Option<String>,
+    pub status: Option<ProcessStatus>,
+    pub exit_code: Option<i32>,
+    /// Substring of a string value in the tool input
+    pub grep: Option<String>,
+    /// Regular expression matched against the string values in the tool input
+    pub regex: Option<String>,
+    /// Print aggregates instead of individual calls
+    pub stats: bool,
+    /// Keep printing calls as they are recorded
+    pub follow: bool,
 }
 
 pub fn execute(args: ToolsArgs) -> Result<()> {
     let conn = db::open_and_init()?;
+    let filter = filter(&args)?;
 
-    let mut calls: Vec<ToolCall> = if let Some(ref branch) = args.branch {
-        let entry = state::find_by_branch(branch)?
-            .ok_or_else(|| crate::error::Error::EntryNotFound(branch.clone()))?;
-        db::get_tool_calls_by_process(&conn, entry.id)?
-    } else {
-        db::get_all_tool_calls(&conn)?
-    };
+    if args.stats {
+        let stats = db::tool_stats(&conn, &filter)?;
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        } else {
+            print_stats(&stats);
+        }
+        return Ok(());
+    }
 
-    // Filter by hook type if specified
-    if let Some(ref hook_type) = args.hook_type {
-        calls.retain(|c| c.hook_type == *hook_type);
+    if args.follow {
+        return follow(&conn, &filter, args.json);
     }
 
-    // Apply limit if specified
-    if let Some(limit) = args.limit {
-        calls.truncate(limit);
+    let mut calls = db::get_tool_calls(&conn, &filter, args.limit)?;
+    // One session reads top to bottom; across sessions the newest come first
+    if filter.process_id.is_none() {
+        calls.reverse();
     }
 
     if args.json {
@@ -39,28 +68,208 @@ pub fn execute(args: ToolsArgs) -> Result<()> {
         }
 
         for call in &calls {
-            let timestamp = call.timestamp.format("%Y-%m-%d %H:%M:%S");
-            let hook_badge = if call.hook_type == "pre" { "PRE " } else { "POST" };
+            print_call(call);
+        }
 
-            println!(
-                "[{}] {} {} {}",
-                timestamp, hook_badge, call.tool_name, call.process_id
-            );
+        let failed = calls.iter().filter(|c| c.is_error == Some(true)).count();
+        println!("Total: {} tool call(s), {} failed", calls.len(), failed);
+    }
 
-            // Parse and pretty-print the input (truncated if too long)
-            if let Ok(input_value) = serde_json::from_str::<serde_json::Value>(&call.tool_input) {
-                let input_str = format_input(&input_value);
-                for line in input_str.lines() {
-                    println!("    {}", line);
+    Ok(())
+}
+
+fn filter(args: &ToolsArgs) -> Result<ToolCallFilter> {
+    let process_id = match args.branch {
+        Some(ref branch) => Some(state::resolve(branch, &args.scope)?.id),
+        None => None,
+    };
+    Ok(ToolCallFilter {
+        process_id,
+        tool_names: args.tools.clone(),
+        hook_type: args.hook_type.clone(),
+        since: args.since,
+        until: args.until,
+        project: args.project.clone(),
+        status: args.status,
+        exit_code: args.exit_code,
+        contains: args.grep.clone(),
+        regex: args.regex.clone(),
+    })
+}
+
+/// Print calls recorded after the newest existing one, using the row id as a
+/// cursor. When following one session, stop once it has terminated, been
+/// removed, or is lost or stale as `wortex wait` classifies it.
+fn follow(conn: &Connection, filter: &ToolCallFilter, json: bool) -> Result<()> {
+    let mut cursor = db::last_tool_call_id(conn)?;
+
+    loop {
+        // Read the status first so calls recorded just before exit are printed
+        let finished = match filter.process_id {
+            Some(id) => match db::get_process_by_id(conn, id)? {
+                Some(entry) => {
+                    let outcome = wait::classify(
+                        &entry,
+                        |e| tmux::window_exists(&e.tmux_session, &e.tmux_window).unwrap_or(false),
+                        state::pid_alive,
+                    );
+                    end_label(outcome, entry.removed_at.is_some()).map(|label| (entry, label))
                 }
+                None => return Ok(()),
+            },
+            None => None,
+        };
+
+        for call in db::get_tool_calls_after(conn, filter, cursor)? {
+            cursor = call.id;
+            if json {
+                println!("{}", serde_json::to_string(&call)?);
+            } else {
+                print_call(&call);
             }
-            println!();
         }
 
-        println!("Total: {} tool call(s)", calls.len());
+        if let Some((entry, label)) = finished {
+            if !json {
+                println!(
+                    "Session '{}' {} (exit code {})",
+                    entry.branch,
+                    label,
+                    entry
+                        .exit_code
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                );
+            }
+            return Ok(());
+        }
+        thread::sleep(FOLLOW_INTERVAL);
     }
+}
 
-    Ok(())
+/// Why following a session stops, or `None` while it is still live.
+fn end_label(outcome: Outcome, removed: bool) -> Option<&'static str> {
+    match outcome {
+        Outcome::Finished(status, _) => Some(status.as_str()),
+        Outcome::Lost => Some("lost"),
+        Outcome::Stale => Some("stale"),
+        Outcome::Pending if removed => Some("removed"),
+        Outcome::Pending => None,
+    }
+}
+
+fn print_call(call: &ToolCall) {
+    let timestamp = call.timestamp.format("%Y-%m-%d %H:%M:%S");
+    let duration = call
+        .duration_ms
+        .map(format_duration)
+        .unwrap_or_else(|| "-".to_string());
+
+    println!(
+        "[{}] {} {} {} {}",
+        timestamp,
+        status_badge(call),
+        call.tool_name,
+        duration,
+        call.process_id
+    );
+
+    // Parse and pretty-print the input (truncated if too long)
+    if let Ok(input_value) = serde_json::from_str::<serde_json::Value>(&call.tool_input) {
+        let input_str = format_input(&input_value);
+        for line in input_str.lines() {
+            println!("    {}", line);
+        }
+    }
+    if let (Some(true), Some(response)) = (call.is_error, &call.tool_response) {
+        println!("    response: {}", truncate_string(response, 100));
+    }
+    if let (Some("deny"), Some(reason)) = (call.decision.as_deref(), &call.decision_reason) {
+        println!("    denied: {}", reason);
+    }
+    println!();
+}
+
+fn print_stats(stats: &ToolStats) {
+    let (Some(first_at), Some(last_at)) = (stats.first_at, stats.last_at) else {
+        println!("No tool calls found.");
+        return;
+    };
+
+    println!(
+        "Calls:    {} ({} failed) in {} session(s)",
+        stats.calls, stats.failed, stats.sessions
+    );
+    println!(
+        "Activity: {} - {} ({:.1} calls/min)",
+        first_at.format("%Y-%m-%d %H:%M:%S"),
+        last_at.format("%Y-%m-%d %H:%M:%S"),
+        stats.calls_per_minute.unwrap_or_default()
+    );
+
+    println!();
+    println!("{:<20} {:>6} {:>6} {:>8}", "TOOL", "CALLS", "FAILED", "AVG TIME");
+    for tool in &stats.tools {
+        let avg = tool
+            .avg_duration_ms
+            .map(|ms| format_duration(ms.round() as i64))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<20} {:>6} {:>6} {:>8}",
+            tool.tool_name, tool.calls, tool.failed, avg
+        );
+    }
+
+    if !stats.files.is_empty() {
+        let count = |f: fn(&db::FileAccess) -> u64| stats.files.iter().filter(|a| f(a) > 0).count();
+        println!();
+        println!(
+            "{} file(s): {} read, {} edited, {} written",
+            stats.files.len(),
+            count(|f| f.read),
+            count(|f| f.edited),
+            count(|f| f.written)
+        );
+        println!("{:>5} {:>5} {:>5}  FILE", "READ", "EDIT", "WRITE");
+        for file in &stats.files {
+            println!(
+                "{:>5} {:>5} {:>5}  {}",
+                file.read, file.edited, file.written, file.path
+            );
+        }
+    }
+
+    if !stats.top_commands.is_empty() {
+        println!();
+        println!("{:>5}  BASH COMMAND", "CALLS");
+        for command in &stats.top_commands {
+            let first_line = command.command.lines().next().unwrap_or_default();
+            println!("{:>5}  {}", command.calls, truncate_string(first_line, 80));
+        }
+    }
+}
+
+/// "OK"/"FAIL" once the PostToolUse result is recorded, "PRE" while pending,
+/// "DENY" when the policy blocked the call.
+fn status_badge(call: &ToolCall) -> &'static str {
+    if call.decision.as_deref() == Some("deny") {
+        return "DENY";
+    }
+    match (call.ended_at, call.is_error) {
+        (Some(_), Some(true)) => "FAIL",
+        (Some(_), _) => "OK  ",
+        (None, _) => "PRE ",
+    }
+}
+
+fn format_duration(ms: i64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000)
+    }
 }
 
 /// Truncate a string to max_chars, handling UTF-8 safely
@@ -108,6 +317,17 @@ mod tests {
     use serde_json::json;
     use uuid::Uuid;
 
+    #[test]
+    fn test_follow_end_label() {
+        let exited = Outcome::Finished(ProcessStatus::Exited, 0);
+        assert_eq!(end_label(exited, false), Some("exited"));
+        assert_eq!(end_label(exited, true), Some("exited"));
+        assert_eq!(end_label(Outcome::Lost, false), Some("lost"));
+        assert_eq!(end_label(Outcome::Stale, false), Some("stale"));
+        assert_eq!(end_label(Outcome::Pending, true), Some("removed"));
+        assert_eq!(end_label(Outcome::Pending, false), None);
+    }
+
     #[test]
     fn test_format_input_simple_object() {
         let value = json!({"command": "ls -la"});
@@ -172,69 +392,42 @@ mod tests {
     }
 
     #[test]
-    fn test_filter_by_hook_type() {
-        let process_id = Uuid::new_v4();
-        let mut calls = vec![
-            ToolCall {
-                id: 1,
-                process_id,
-                hook_type: "pre".to_string(),
-                tool_name: "Read".to_string(),
-                tool_input: "{}".to_string(),
-                timestamp: Utc::now(),
-                sequence: 1,
-            },
-            ToolCall {
-                id: 2,
-                process_id,
-                hook_type: "post".to_string(),
-                tool_name: "Read".to_string(),
-                tool_input: "{}".to_string(),
-                timestamp: Utc::now(),
-                sequence: 2,
-            },
-            ToolCall {
-                id: 3,
-                process_id,
-                hook_type: "pre".to_string(),
-                tool_name: "Write".to_string(),
-                tool_input: "{}".to_string(),
-                timestamp: Utc::now(),
-                sequence: 3,
-            },
-        ];
-
-        // Simulate filter logic from execute()
-        let hook_type = Some("pre".to_string());
-        if let Some(ref ht) = hook_type {
-            calls.retain(|c| c.hook_type == *ht);
-        }
-
-        assert_eq!(calls.len(), 2);
-        assert!(calls.iter().all(|c| c.hook_type == "pre"));
+    fn test_format_duration() {
+        assert_eq!(format_duration(340), "340ms");
+        assert_eq!(format_duration(1250), "1.2s");
+        assert_eq!(format_duration(125_000), "2m05s");
     }
 
     #[test]
-    fn test_limit_truncates_results() {
-        let process_id = Uuid::new_v4();
-        let mut calls: Vec<ToolCall> = (0..10)
-            .map(|i| ToolCall {
-                id: i,
-                process_id,
-                hook_type: "pre".to_string(),
-                tool_name: format!("Tool{}", i),
-                tool_input: "{}".to_string(),
-                timestamp: Utc::now(),
-                sequence: i + 1,
-            })
-            .collect();
-
-        // Simulate limit logic from execute()
-        let limit = Some(3);
-        if let Some(l) = limit {
-            calls.truncate(l);
-        }
+    fn test_status_badge() {
+        let mut call = ToolCall {
+            id: 1,
+            process_id: Uuid::new_v4(),
+            hook_type: "pre".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: "{}".to_string(),
+            timestamp: Utc::now(),
+            sequence: 1,
+            tool_use_id: Some("toolu_1".to_string()),
+            ended_at: None,
+            duration_ms: None,
+            is_error: None,
+            tool_response: None,
+            decision: None,
+            decision_reason: None,
+        };
+        assert_eq!(status_badge(&call), "PRE ");
+
+        call.ended_at = Some(Utc::now());
+        call.is_error = Some(false);
+        assert_eq!(status_badge(&call), "OK  ");
+
+        call.is_error = Some(true);
+        assert_eq!(status_badge(&call), "FAIL");
 
-        assert_eq!(calls.len(), 3);
+        call.ended_at = None;
+        call.is_error = None;
+        call.decision = Some("deny".to_string());
+        assert_eq!(status_badge(&call), "DENY");
     }
 }
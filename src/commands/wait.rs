@@ -0,0 +1,280 @@
+use crate::error::{Error, Result};
+use crate::notify;
+use crate::state::{self, Command, Entry, ProcessStatus, ProjectScope};
+use crate::tmux;
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Exit code when `--timeout` expires, as with timeout(1).
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+pub struct WaitArgs {
+    pub branches: Vec<String>,
+    pub scope: ProjectScope,
+    /// Return when the first session finishes instead of waiting for all
+    pub any: bool,
+    pub timeout: Option<Duration>,
+    pub json: bool,
+}
+
+/// Where a waited-on session stands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Still spawned, running or waiting
+    Pending,
+    /// Terminated; `__run` recorded its status and exit code
+    Finished(ProcessStatus, i32),
+    /// Recorded as live but its PID is gone (supervisor died)
+    Lost,
+    /// Never started and its window is gone
+    Stale,
+}
+
+impl Outcome {
+    pub fn is_done(&self) -> bool {
+        *self != Outcome::Pending
+    }
+
+    /// Exit code this session contributes; lost and stale sessions count as failed.
+    fn exit_code(&self) -> Option<i32> {
+        match self {
+            Outcome::Pending => None,
+            Outcome::Finished(_, code) => Some(*code),
+            Outcome::Lost | Outcome::Stale => Some(1),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WaitResult {
+    id: Uuid,
+    project: String,
+    branch: String,
+    status: String,
+    exit_code: Option<i32>,
+    finished: bool,
+}
+
+pub fn execute(args: WaitArgs) -> Result<()> {
+    // Include archived entries: exit-kill archives an entry as soon as it
+    // finishes, and its row keeps the final status
+    let mut entries: Vec<Entry> = Vec::new();
+    for branch in &args.branches {
+        let entry = state::resolve_including_removed(branch, &args.scope)?;
+        if matches!(entry.command, Command::External) {
+            return Err(Error::ExternalEntry(entry.branch.clone()));
+        }
+        if !entries.iter().any(|e| e.id == entry.id) {
+            entries.push(entry);
+        }
+    }
+
+    let started = Instant::now();
+    let mut outcomes = vec![Outcome::Pending; entries.len()];
+    let mut timed_out = false;
+
+    loop {
+        for (entry, outcome) in entries.iter_mut().zip(outcomes.iter_mut()) {
+            if outcome.is_done() {
+                continue;
+            }
+            if let Some(current) = state::find_by_id(entry.id)? {
+                *entry = current;
+            }
+            *outcome = classify(
+                entry,
+                |e| tmux::window_exists(&e.tmux_session, &e.tmux_window).unwrap_or(false),
+                state::pid_alive,
+            );
+        }
+
+        let done = outcomes.iter().filter(|o| o.is_done()).count();
+        if done == outcomes.len() || (args.any && done > 0) {
+            break;
+        }
+        if args.timeout.is_some_and(|t| started.elapsed() >= t) {
+            timed_out = true;
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    if timed_out {
+        for (entry, _) in entries.iter().zip(&outcomes).filter(|(_, o)| !o.is_done()) {
+            notify::emit(notify::Event::Timeout, entry, None);
+        }
+    }
+
+    let results: Vec<WaitResult> = entries
+        .iter()
+        .zip(&outcomes)
+        .map(|(entry, outcome)| WaitResult {
+            id: entry.id,
+            project: entry.project.clone(),
+            branch: entry.branch.clone(),
+            status: status_label(entry, *outcome).to_string(),
+            exit_code: match outcome {
+                Outcome::Finished(_, code) => Some(*code),
+                _ => None,
+            },
+            finished: outcome.is_done(),
+        })
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        println!("{:<8} {:<20} {:<10} {:<5}", "PROJECT", "BRANCH", "STATUS", "EXIT");
+        for result in &results {
+            let exit_str = result
+                .exit_code
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "{:<8} {:<20} {:<10} {:<5}",
+                result.project, result.branch, result.status, exit_str
+            );
+        }
+        if timed_out {
+            println!();
+            println!("Timed out after {}s", started.elapsed().as_secs());
+        }
+    }
+
+    std::process::exit(summary_code(&outcomes, args.any, timed_out));
+}
+
+/// Classify an entry from its recorded status, downgrading it the same way
+/// `wortex list` does when the recorded state can no longer be true.
+pub fn classify<F, G>(entry: &Entry, window_exists: F, pid_alive: G) -> Outcome
+where
+    F: Fn(&Entry) -> bool,
+    G: Fn(u32) -> bool,
+{
+    match entry.status {
+        ProcessStatus::Exited | ProcessStatus::Killed | ProcessStatus::Signaled => {
+            Outcome::Finished(entry.status, entry.exit_code.unwrap_or(1))
+        }
+        ProcessStatus::Spawned if !window_exists(entry) => Outcome::Stale,
+        ProcessStatus::Running | ProcessStatus::Waiting
+            if entry.pid.is_some_and(|pid| !pid_alive(pid)) =>
+        {
+            Outcome::Lost
+        }
+        _ => Outcome::Pending,
+    }
+}
+
+fn status_label(entry: &Entry, outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Finished(status, _) => status.as_str(),
+        Outcome::Lost => "lost",
+        Outcome::Stale => "stale",
+        Outcome::Pending => entry.status.as_str(),
+    }
+}
+
+/// Process exit code for the whole wait:
+/// - timeout: 124
+/// - one session: its exit code
+/// - `--any`: the exit code of the first finished session
+/// - several sessions: 0 if all succeeded, else 1
+fn summary_code(outcomes: &[Outcome], any: bool, timed_out: bool) -> i32 {
+    if timed_out {
+        return TIMEOUT_EXIT_CODE;
+    }
+    if outcomes.len() == 1 || any {
+        return outcomes
+            .iter()
+            .find_map(Outcome::exit_code)
+            .unwrap_or(TIMEOUT_EXIT_CODE);
+    }
+    if outcomes.iter().all(|o| o.exit_code() == Some(0)) {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_entry(status: ProcessStatus, pid: Option<u32>, exit_code: Option<i32>) -> Entry {
+        Entry {
+            exit_code,
+            status,
+            pid,
+            run_count: 1,
+            ..Entry::for_test("mp", "fix-ci")
+        }
+    }
+
+    #[test]
+    fn test_classify_finished() {
+        let entry = make_entry(ProcessStatus::Exited, Some(42), Some(3));
+        assert_eq!(
+            classify(&entry, |_| false, |_| false),
+            Outcome::Finished(ProcessStatus::Exited, 3)
+        );
+
+        let entry = make_entry(ProcessStatus::Signaled, Some(42), Some(143));
+        assert_eq!(
+            classify(&entry, |_| true, |_| true),
+            Outcome::Finished(ProcessStatus::Signaled, 143)
+        );
+    }
+
+    #[test]
+    fn test_classify_archived_entry_keeps_final_status() {
+        // Exit-kill archives the entry and kills its window right after exit
+        let mut entry = make_entry(ProcessStatus::Exited, Some(42), Some(0));
+        entry.removed_at = Some(Utc::now());
+        assert_eq!(
+            classify(&entry, |_| false, |_| false),
+            Outcome::Finished(ProcessStatus::Exited, 0)
+        );
+    }
+
+    #[test]
+    fn test_classify_pending_lost_and_stale() {
+        let running = make_entry(ProcessStatus::Running, Some(42), None);
+        assert_eq!(classify(&running, |_| true, |_| true), Outcome::Pending);
+        assert_eq!(classify(&running, |_| true, |_| false), Outcome::Lost);
+
+        let waiting = make_entry(ProcessStatus::Waiting, Some(42), None);
+        assert_eq!(classify(&waiting, |_| true, |_| true), Outcome::Pending);
+
+        let spawned = make_entry(ProcessStatus::Spawned, None, None);
+        assert_eq!(classify(&spawned, |_| true, |_| true), Outcome::Pending);
+        assert_eq!(classify(&spawned, |_| false, |_| true), Outcome::Stale);
+    }
+
+    #[test]
+    fn test_summary_code_single() {
+        let done = Outcome::Finished(ProcessStatus::Exited, 3);
+        assert_eq!(summary_code(&[done], false, false), 3);
+        assert_eq!(summary_code(&[Outcome::Lost], false, false), 1);
+        assert_eq!(summary_code(&[Outcome::Pending], false, true), 124);
+    }
+
+    #[test]
+    fn test_summary_code_all() {
+        let ok = Outcome::Finished(ProcessStatus::Exited, 0);
+        let failed = Outcome::Finished(ProcessStatus::Exited, 2);
+        assert_eq!(summary_code(&[ok, ok], false, false), 0);
+        assert_eq!(summary_code(&[ok, failed], false, false), 1);
+        assert_eq!(summary_code(&[ok, Outcome::Stale], false, false), 1);
+    }
+
+    #[test]
+    fn test_summary_code_any() {
+        let failed = Outcome::Finished(ProcessStatus::Exited, 2);
+        assert_eq!(summary_code(&[Outcome::Pending, failed], true, false), 2);
+    }
+}
@@ -0,0 +1,472 @@
+use crate::db;
+use crate::error::{Error, Result};
+use crate::git;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Effective configuration after layering. Precedence (highest first):
+/// CLI flags > repo `.wortex.toml` > global `~/.wortex/config.toml` > built-in defaults.
+/// CLI flags are applied by the callers; every field here is optional so an
+/// unset value falls through to the next layer.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub new: NewConfig,
+    pub worktree: WorktreeConfig,
+    pub agent: AgentConfig,
+    pub kill: KillConfig,
+    pub list: ListConfig,
+    pub notify: NotifyConfig,
+    pub policy: PolicyConfig,
+}
+
+/// Defaults for `wortex new`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NewConfig {
+    pub remote: Option<String>,
+    pub base: Option<String>,
+    pub agent: Option<String>,
+    /// Same syntax as `--exit-kill`: "" (exit 0), "any", or "0,1"
+    pub exit_kill: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WorktreeConfig {
+    /// Directory new worktrees are created in (default: the repo's parent directory)
+    pub dir: Option<String>,
+    /// Worktree path, relative to `dir` unless absolute. See `commands::new::render_path_template`.
+    pub path_template: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Binary used to run prompts (default: `claude`)
+    pub binary: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct KillConfig {
+    pub keep_worktree: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ListConfig {
+    pub json: Option<bool>,
+}
+
+/// Lifecycle notifications. Each event takes a comma-separated list of
+/// channels (`tmux`, `bell`, `command`); "" or "none" turns it off.
+/// See `notify::Channel`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub exited_ok: Option<String>,
+    pub exited_fail: Option<String>,
+    pub waiting: Option<String>,
+    pub timeout: Option<String>,
+    /// Shell command run for the `command` channel; gets the event as JSON on stdin
+    pub command: Option<String>,
+}
+
+/// Tool call policy enforced by the PreToolUse hook. See `policy::Policy`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Policy file (default: `~/.wortex/policy.toml`)
+    pub file: Option<String>,
+}
+
+impl Config {
+    pub fn remote(&self) -> &str {
+        self.new.remote.as_deref().unwrap_or(DEFAULT_REMOTE)
+    }
+
+    pub fn base(&self) -> &str {
+        self.new.base.as_deref().unwrap_or(DEFAULT_BASE)
+    }
+
+    pub fn agent_binary(&self) -> &str {
+        self.agent.binary.as_deref().unwrap_or(DEFAULT_AGENT_BINARY)
+    }
+
+    /// Directory new worktrees are created in, `~` expanded. `None` means
+    /// "next to the main repo".
+    pub fn worktree_dir(&self) -> Option<PathBuf> {
+        self.worktree.dir.as_deref().map(expand_tilde)
+    }
+
+    /// Configured policy file, `~` expanded. `None` means the default.
+    pub fn policy_file(&self) -> Option<PathBuf> {
+        self.policy.file.as_deref().map(expand_tilde)
+    }
+
+    pub fn path_template(&self) -> &str {
+        self.worktree
+            .path_template
+            .as_deref()
+            .unwrap_or(DEFAULT_PATH_TEMPLATE)
+    }
+}
+
+pub const DEFAULT_REMOTE: &str = "origin";
+pub const DEFAULT_BASE: &str = "main";
+pub const DEFAULT_AGENT_BINARY: &str = "claude";
+pub const DEFAULT_PATH_TEMPLATE: &str = "{project}-{branch_slug}";
+pub const DEFAULT_NOTIFY_EXITED_OK: &str = "tmux";
+pub const DEFAULT_NOTIFY_ALERT: &str = "tmux,bell,command";
+pub const DEFAULT_POLICY_FILE: &str = "~/.wortex/policy.toml";
+
+// ---------------------------------------------------------------------------
+// Known keys (used by `wortex config`)
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyKind {
+    String,
+    Bool,
+}
+
+pub struct ConfigKey {
+    pub name: &'static str,
+    pub kind: KeyKind,
+    pub default: Option<&'static str>,
+}
+
+pub const KEYS: &[ConfigKey] = &[
+    ConfigKey { name: "new.remote", kind: KeyKind::String, default: Some(DEFAULT_REMOTE) },
+    ConfigKey { name: "new.base", kind: KeyKind::String, default: Some(DEFAULT_BASE) },
+    ConfigKey { name: "new.agent", kind: KeyKind::String, default: None },
+    ConfigKey { name: "new.exit_kill", kind: KeyKind::String, default: None },
+    ConfigKey { name: "worktree.dir", kind: KeyKind::String, default: None },
+    ConfigKey { name: "worktree.path_template", kind: KeyKind::String, default: Some(DEFAULT_PATH_TEMPLATE) },
+    ConfigKey { name: "agent.binary", kind: KeyKind::String, default: Some(DEFAULT_AGENT_BINARY) },
+    ConfigKey { name: "kill.keep_worktree", kind: KeyKind::Bool, default: Some("false") },
+    ConfigKey { name: "list.json", kind: KeyKind::Bool, default: Some("false") },
+    ConfigKey { name: "notify.exited_ok", kind: KeyKind::String, default: Some(DEFAULT_NOTIFY_EXITED_OK) },
+    ConfigKey { name: "notify.exited_fail", kind: KeyKind::String, default: Some(DEFAULT_NOTIFY_ALERT) },
+    ConfigKey { name: "notify.waiting", kind: KeyKind::String, default: Some(DEFAULT_NOTIFY_ALERT) },
+    ConfigKey { name: "notify.timeout", kind: KeyKind::String, default: Some(DEFAULT_NOTIFY_ALERT) },
+    ConfigKey { name: "notify.command", kind: KeyKind::String, default: None },
+    ConfigKey { name: "policy.file", kind: KeyKind::String, default: Some(DEFAULT_POLICY_FILE) },
+];
+
+pub fn find_key(name: &str) -> Result<&'static ConfigKey> {
+    KEYS.iter()
+        .find(|k| k.name == name)
+        .ok_or_else(|| Error::Config(format!("unknown key '{}'", name)))
+}
+
+// ---------------------------------------------------------------------------
+// Paths
+// ---------------------------------------------------------------------------
+
+pub const REPO_CONFIG_FILE: &str = ".wortex.toml";
+
+pub fn global_config_path() -> Result<PathBuf> {
+    Ok(db::wortex_dir()?.join("config.toml"))
+}
+
+/// `.wortex.toml` at the root of the main repo containing `dir`, if `dir` is in a repo.
+pub fn repo_config_path(dir: &Path) -> Option<PathBuf> {
+    git::main_repo_root(dir)
+        .ok()
+        .map(|root| root.join(REPO_CONFIG_FILE))
+}
+
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if path == "~" => dirs::home_dir().unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Loading
+// ---------------------------------------------------------------------------
+
+/// Load the layered configuration for the repo containing `dir`.
+pub fn load(dir: &Path) -> Result<Config> {
+    let global = global_config_path()?;
+    let repo = repo_config_path(dir);
+    load_files(Some(&global), repo.as_deref())
+}
+
+pub fn load_files(global: Option<&Path>, repo: Option<&Path>) -> Result<Config> {
+    let mut merged = toml::Table::new();
+    for path in [global, repo].into_iter().flatten() {
+        merge_tables(&mut merged, read_table(path)?);
+    }
+    toml::Value::Table(merged)
+        .try_into()
+        .map_err(|e: toml::de::Error| Error::Config(e.to_string()))
+}
+
+/// Read a config file as a raw table. A missing file is an empty table.
+pub fn read_table(path: &Path) -> Result<toml::Table> {
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    content
+        .parse::<toml::Table>()
+        .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+}
+
+pub fn write_table(path: &Path, table: &toml::Table) -> Result<()> {
+    let content = toml::to_string_pretty(table).map_err(|e| Error::Config(e.to_string()))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Deep-merge `overlay` into `base`; values in `overlay` win.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_sub)), toml::Value::Table(overlay_sub)) => {
+                merge_tables(base_sub, overlay_sub);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dotted key access
+// ---------------------------------------------------------------------------
+
+pub fn get_value<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (section, name) = key.split_once('.')?;
+    table.get(section)?.as_table()?.get(name)
+}
+
+pub fn set_value(table: &mut toml::Table, key: &ConfigKey, raw: &str) -> Result<()> {
+    let value = match key.kind {
+        KeyKind::String => toml::Value::String(raw.to_string()),
+        KeyKind::Bool => match raw {
+            "true" => toml::Value::Boolean(true),
+            "false" => toml::Value::Boolean(false),
+            _ => {
+                return Err(Error::Config(format!(
+                    "'{}' expects true or false, got '{}'",
+                    key.name, raw
+                )))
+            }
+        },
+    };
+
+    let (section, name) = key
+        .name
+        .split_once('.')
+        .expect("config keys are section.name");
+    let section = table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    match section {
+        toml::Value::Table(t) => {
+            t.insert(name.to_string(), value);
+            Ok(())
+        }
+        _ => Err(Error::Config(format!("'{}' is not a table", key.name))),
+    }
+}
+
+pub fn unset_value(table: &mut toml::Table, key: &str) -> bool {
+    let Some((section, name)) = key.split_once('.') else {
+        return false;
+    };
+    let removed = table
+        .get_mut(section)
+        .and_then(|v| v.as_table_mut())
+        .map(|t| t.remove(name).is_some())
+        .unwrap_or(false);
+    if table
+        .get(section)
+        .and_then(|v| v.as_table())
+        .is_some_and(|t| t.is_empty())
+    {
+        table.remove(section);
+    }
+    removed
+}
+
+/// Render a value the way `config get` prints it (strings unquoted).
+pub fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_defaults_when_no_files() {
+        let config = load_files(None, None).unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.remote(), "origin");
+        assert_eq!(config.base(), "main");
+        assert_eq!(config.agent_binary(), "claude");
+        assert!(config.worktree_dir().is_none());
+        assert_eq!(config.path_template(), "{project}-{branch_slug}");
+    }
+
+    #[test]
+    fn test_missing_files_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let config = load_files(
+            Some(&dir.path().join("nope.toml")),
+            Some(&dir.path().join("also-nope.toml")),
+        )
+        .unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_repo_overrides_global() {
+        let dir = TempDir::new().unwrap();
+        let global = write(
+            &dir,
+            "global.toml",
+            "[new]\nremote = \"upstream\"\nbase = \"develop\"\n\n[agent]\nbinary = \"my-claude\"\n",
+        );
+        let repo = write(&dir, "repo.toml", "[new]\nbase = \"trunk\"\n");
+
+        let config = load_files(Some(&global), Some(&repo)).unwrap();
+        assert_eq!(config.remote(), "upstream");
+        assert_eq!(config.base(), "trunk");
+        assert_eq!(config.agent_binary(), "my-claude");
+    }
+
+    #[test]
+    fn test_per_command_settings() {
+        let dir = TempDir::new().unwrap();
+        let global = write(
+            &dir,
+            "global.toml",
+            "[kill]\nkeep_worktree = true\n\n[list]\njson = true\n",
+        );
+        let config = load_files(Some(&global), None).unwrap();
+        assert_eq!(config.kill.keep_worktree, Some(true));
+        assert_eq!(config.list.json, Some(true));
+    }
+
+    #[test]
+    fn test_notify_settings() {
+        let dir = TempDir::new().unwrap();
+        let global = write(
+            &dir,
+            "global.toml",
+            "[notify]\nexited_ok = \"none\"\ncommand = \"~/bin/on-event\"\n",
+        );
+        let config = load_files(Some(&global), None).unwrap();
+        assert_eq!(config.notify.exited_ok.as_deref(), Some("none"));
+        assert_eq!(config.notify.waiting, None);
+        assert_eq!(config.notify.command.as_deref(), Some("~/bin/on-event"));
+    }
+
+    #[test]
+    fn test_invalid_toml_is_config_error() {
+        let dir = TempDir::new().unwrap();
+        let global = write(&dir, "global.toml", "[new\nremote = ");
+        assert!(matches!(
+            load_files(Some(&global), None),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_worktree_dir_expands_tilde() {
+        let config = Config {
+            worktree: WorktreeConfig {
+                dir: Some("~/worktrees".to_string()),
+                path_template: None,
+            },
+            ..Config::default()
+        };
+        let expected = dirs::home_dir().unwrap().join("worktrees");
+        assert_eq!(config.worktree_dir(), Some(expected));
+
+        let config = Config {
+            policy: PolicyConfig {
+                file: Some("~/policy.toml".to_string()),
+            },
+            ..Config::default()
+        };
+        let expected = dirs::home_dir().unwrap().join("policy.toml");
+        assert_eq!(config.policy_file(), Some(expected));
+    }
+
+    #[test]
+    fn test_set_and_get_value() {
+        let mut table = toml::Table::new();
+        set_value(&mut table, find_key("new.base").unwrap(), "develop").unwrap();
+        set_value(&mut table, find_key("kill.keep_worktree").unwrap(), "true").unwrap();
+
+        assert_eq!(
+            get_value(&table, "new.base"),
+            Some(&toml::Value::String("develop".to_string()))
+        );
+        assert_eq!(
+            get_value(&table, "kill.keep_worktree"),
+            Some(&toml::Value::Boolean(true))
+        );
+        assert_eq!(get_value(&table, "new.remote"), None);
+    }
+
+    #[test]
+    fn test_set_bool_rejects_garbage() {
+        let mut table = toml::Table::new();
+        let key = find_key("list.json").unwrap();
+        assert!(set_value(&mut table, key, "yes").is_err());
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        assert!(find_key("new.nope").is_err());
+        assert!(find_key("remote").is_err());
+    }
+
+    #[test]
+    fn test_unset_value_drops_empty_section() {
+        let mut table = toml::Table::new();
+        set_value(&mut table, find_key("new.base").unwrap(), "develop").unwrap();
+
+        assert!(unset_value(&mut table, "new.base"));
+        assert!(table.is_empty());
+        assert!(!unset_value(&mut table, "new.base"));
+    }
+
+    #[test]
+    fn test_write_then_load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let mut table = read_table(&path).unwrap();
+        set_value(&mut table, find_key("new.remote").unwrap(), "fork").unwrap();
+        write_table(&path, &table).unwrap();
+
+        let config = load_files(Some(&path), None).unwrap();
+        assert_eq!(config.remote(), "fork");
+    }
+}
@@ -1,8 +1,11 @@
 use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use regex::Regex;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 // ---------------------------------------------------------------------------
@@ -18,6 +21,172 @@ pub struct ToolCall {
     pub tool_input: String,
     pub timestamp: DateTime<Utc>,
     pub sequence: i64,
+    /// Claude's id for the invocation, used to pair PreToolUse with PostToolUse
+    pub tool_use_id: Option<String>,
+    /// Set when the PostToolUse hook for this call has been recorded
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub is_error: Option<bool>,
+    /// Tool response, truncated to `MAX_RESPONSE_CHARS`
+    pub tool_response: Option<String>,
+    /// Policy decision ("allow" or "deny") made in the PreToolUse hook; unset
+    /// when no policy applied
+    pub decision: Option<String>,
+    pub decision_reason: Option<String>,
+}
+
+/// Which tool calls a query returns; every field that is set narrows the
+/// result. Translated to SQL by `to_sql`.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallFilter {
+    pub process_id: Option<Uuid>,
+    /// Any of these tool names
+    pub tool_names: Vec<String>,
+    pub hook_type: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Calls of sessions in this project
+    pub project: Option<String>,
+    /// Calls of sessions with this status
+    pub status: Option<ProcessStatus>,
+    /// Calls of sessions that exited with this code
+    pub exit_code: Option<i32>,
+    /// Substring of a string value in `tool_input`
+    pub contains: Option<String>,
+    /// Regular expression matched against the string values in `tool_input`
+    pub regex: Option<String>,
+}
+
+impl ToolCallFilter {
+    /// WHERE condition over `tool_calls` and its positional parameters.
+    fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut conditions: Vec<String> = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(id) = self.process_id {
+            conditions.push("process_id = ?".to_string());
+            params.push(Value::Text(id.to_string()));
+        }
+        if !self.tool_names.is_empty() {
+            let placeholders = vec!["?"; self.tool_names.len()].join(", ");
+            conditions.push(format!("tool_name IN ({})", placeholders));
+            params.extend(self.tool_names.iter().cloned().map(Value::Text));
+        }
+        if let Some(ref hook_type) = self.hook_type {
+            conditions.push("hook_type = ?".to_string());
+            params.push(Value::Text(hook_type.clone()));
+        }
+        // Timestamps are stored as RFC 3339 in UTC, which sorts as text
+        if let Some(since) = self.since {
+            conditions.push("timestamp >= ?".to_string());
+            params.push(Value::Text(since.to_rfc3339()));
+        }
+        if let Some(until) = self.until {
+            conditions.push("timestamp <= ?".to_string());
+            params.push(Value::Text(until.to_rfc3339()));
+        }
+        if let Some(ref contains) = self.contains {
+            conditions.push(any_input_string("instr(value, ?) > 0"));
+            params.push(Value::Text(contains.clone()));
+        }
+        if let Some(ref regex) = self.regex {
+            conditions.push(any_input_string("value REGEXP ?"));
+            params.push(Value::Text(regex.clone()));
+        }
+
+        let mut session: Vec<&str> = Vec::new();
+        if let Some(ref project) = self.project {
+            session.push("project = ?");
+            params.push(Value::Text(project.clone()));
+        }
+        if let Some(status) = self.status {
+            session.push("status = ?");
+            params.push(Value::Text(status.as_str().to_string()));
+        }
+        if let Some(code) = self.exit_code {
+            session.push("exit_code = ?");
+            params.push(Value::Integer(code.into()));
+        }
+        if !session.is_empty() {
+            conditions.push(format!(
+                "process_id IN (SELECT id FROM processes WHERE {})",
+                session.join(" AND ")
+            ));
+        }
+
+        if conditions.is_empty() {
+            ("1 = 1".to_string(), params)
+        } else {
+            (conditions.join(" AND "), params)
+        }
+    }
+}
+
+/// True when `predicate` holds for some decoded string value in `tool_input`,
+/// so a pattern sees `a "b"` rather than `a \"b\"`. Input that is not valid
+/// JSON is matched as one string.
+fn any_input_string(predicate: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM json_tree(CASE WHEN json_valid(tool_input) THEN tool_input \
+         ELSE json_quote(tool_input) END) WHERE type = 'text' AND {})",
+        predicate
+    )
+}
+
+/// Aggregates over a set of tool calls, computed in SQL by `tool_stats`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub failed: u64,
+    /// Number of sessions the calls belong to
+    pub sessions: u64,
+    pub first_at: Option<DateTime<Utc>>,
+    /// Latest start or end of a call
+    pub last_at: Option<DateTime<Utc>>,
+    /// Calls over the active span, counted as at least one minute
+    pub calls_per_minute: Option<f64>,
+    pub tools: Vec<ToolCount>,
+    pub files: Vec<FileAccess>,
+    /// Most frequent Bash commands, at most `TOP_COMMANDS`
+    pub top_commands: Vec<CommandCount>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolCount {
+    pub tool_name: String,
+    pub calls: u64,
+    pub failed: u64,
+    pub avg_duration_ms: Option<f64>,
+}
+
+/// How often a file was passed as `file_path` to Read, Edit/MultiEdit and Write.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileAccess {
+    pub path: String,
+    pub read: u64,
+    pub edited: u64,
+    pub written: u64,
+}
+
+/// A file edited by several live sessions of one repo; one value per
+/// session. Paths are relative to the session's worktree.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SharedEdit {
+    /// The repo's shared git directory, or the project for older entries
+    pub repo: String,
+    pub project: String,
+    pub path: String,
+    pub process_id: Uuid,
+    pub branch: String,
+    /// Edit, MultiEdit and Write calls on the file
+    pub edits: u64,
+    pub last_edit_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CommandCount {
+    pub command: String,
+    pub calls: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -76,15 +245,37 @@ fn configure_connection(conn: &Connection) -> Result<()> {
          PRAGMA foreign_keys = ON;",
     )
     .map_err(|e| Error::Database(e.to_string()))?;
+
+    // `X REGEXP Y` calls regexp(Y, X); the compiled pattern is cached per statement
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            type BoxError = Box<dyn std::error::Error + Send + Sync>;
+            let regex = ctx.get_or_create_aux(0, |pattern| -> std::result::Result<_, BoxError> {
+                Ok(Regex::new(pattern.as_str()?)?)
+            })?;
+            let text: Option<String> = ctx.get(1)?;
+            Ok(text.is_some_and(|t| regex.is_match(&t)))
+        },
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
     Ok(())
 }
 
-/// Initialize schema on an arbitrary connection (used for testing with in-memory DBs).
-pub fn init_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS processes (
+/// Column definitions for the processes table, shared by `init_schema` and the
+/// table rebuild in `drop_unique_process_name`.
+///
+/// Entries are identified by their repo and branch. The repo is its shared
+/// git directory (`git_common_dir`), since two repos can derive the same
+/// project prefix; rows recorded before it was stored fall back to `project`.
+/// `name` is "project:branch" and only kept for display. Uniqueness among
+/// live entries lives in `idx_processes_active_repo_branch`; removed entries
+/// keep their row (with `removed_at` set) so their logs stay reachable.
+const PROCESSES_DDL: &str = "
             id              TEXT PRIMARY KEY,
-            name            TEXT UNIQUE NOT NULL,
+            name            TEXT NOT NULL,
             project         TEXT NOT NULL,
             directory       TEXT NOT NULL,
             branch          TEXT,
@@ -99,7 +290,12 @@ pub fn init_schema(conn: &Connection) -> Result<()> {
             prompt          TEXT,
             created_at      TEXT NOT NULL,
             updated_at      TEXT NOT NULL
-        );
+        ";
+
+/// Initialize schema on an arbitrary connection (used for testing with in-memory DBs).
+pub fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS processes ({});
 
         CREATE TABLE IF NOT EXISTS tool_calls (
             id          INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -108,13 +304,161 @@ pub fn init_schema(conn: &Connection) -> Result<()> {
             tool_input  TEXT,
             hook_type   TEXT NOT NULL,
             timestamp   TEXT NOT NULL,
-            sequence    INTEGER NOT NULL
+            sequence    INTEGER NOT NULL,
+            tool_use_id TEXT,
+            ended_at    TEXT,
+            duration_ms INTEGER,
+            is_error    INTEGER,
+            tool_response TEXT,
+            decision    TEXT,
+            decision_reason TEXT
         );
 
         CREATE INDEX IF NOT EXISTS idx_tool_calls_process_id
-            ON tool_calls(process_id);",
+            ON tool_calls(process_id);
+
+        CREATE TABLE IF NOT EXISTS runs (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            process_id  TEXT NOT NULL REFERENCES processes(id),
+            run_number  INTEGER NOT NULL,
+            pid         INTEGER,
+            status      TEXT NOT NULL,
+            exit_code   INTEGER,
+            started_at  TEXT NOT NULL,
+            ended_at    TEXT,
+            UNIQUE(process_id, run_number)
+        );
+
+        CREATE TABLE IF NOT EXISTS events (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            process_id  TEXT NOT NULL REFERENCES processes(id),
+            kind        TEXT NOT NULL,
+            detail      TEXT,
+            timestamp   TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_events_process_id
+            ON events(process_id);",
+        PROCESSES_DDL
+    ))
+    .map_err(|e| Error::Database(e.to_string()))?;
+    migrate_schema(conn)?;
+    Ok(())
+}
+
+/// Bring databases created by older versions up to the current schema.
+/// Every step is idempotent so this can run on each open.
+fn migrate_schema(conn: &Connection) -> Result<()> {
+    // Must run before any `ensure_column` on processes: the rebuild only
+    // copies the original columns.
+    drop_unique_process_name(conn)?;
+
+    ensure_column(conn, "processes", "repo_path", "TEXT")?;
+    ensure_column(conn, "processes", "git_common_dir", "TEXT")?;
+    ensure_column(conn, "processes", "removed_at", "TEXT")?;
+    ensure_column(conn, "processes", "run_count", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "processes", "remote", "TEXT")?;
+    ensure_column(conn, "processes", "base", "TEXT")?;
+    ensure_column(conn, "processes", "pushed_sha", "TEXT")?;
+    ensure_column(conn, "processes", "pushed_at", "TEXT")?;
+    // Older entries predate `--existing`: their branches were created by
+    // wortex, except those of adopted worktrees
+    ensure_column(conn, "processes", "created_branch", "INTEGER NOT NULL DEFAULT 1")?;
+    conn.execute(
+        "UPDATE processes SET created_branch = 0 WHERE status = 'external' AND created_branch = 1",
+        [],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_processes_project_branch;
+         DROP INDEX IF EXISTS idx_processes_active_project_branch;
+         CREATE UNIQUE INDEX IF NOT EXISTS idx_processes_active_repo_branch
+            ON processes(COALESCE(git_common_dir, project), branch) WHERE removed_at IS NULL;",
     )
     .map_err(|e| Error::Database(e.to_string()))?;
+
+    ensure_column(conn, "tool_calls", "tool_use_id", "TEXT")?;
+    ensure_column(conn, "tool_calls", "ended_at", "TEXT")?;
+    ensure_column(conn, "tool_calls", "duration_ms", "INTEGER")?;
+    ensure_column(conn, "tool_calls", "is_error", "INTEGER")?;
+    ensure_column(conn, "tool_calls", "tool_response", "TEXT")?;
+    ensure_column(conn, "tool_calls", "decision", "TEXT")?;
+    ensure_column(conn, "tool_calls", "decision_reason", "TEXT")?;
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tool_calls_tool_use_id
+            ON tool_calls(process_id, tool_use_id);
+         CREATE INDEX IF NOT EXISTS idx_tool_calls_tool_name
+            ON tool_calls(tool_name);
+         CREATE INDEX IF NOT EXISTS idx_tool_calls_timestamp
+            ON tool_calls(timestamp);
+         CREATE INDEX IF NOT EXISTS idx_processes_project
+            ON processes(project);",
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Older databases declared `name TEXT UNIQUE` with name = branch, so the same
+/// branch could not be tracked in two repos. SQLite cannot drop a constraint,
+/// so rebuild the table and rewrite `name` as "project:branch".
+fn drop_unique_process_name(conn: &Connection) -> Result<()> {
+    let has_unique: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_index_list('processes') WHERE origin = 'u'",
+            [],
+            |row| {
+                let count: i64 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    if !has_unique {
+        return Ok(());
+    }
+
+    let columns = "id, name, project, directory, branch, tmux_session, tmux_window, pid, status,
+         blocked_on, exit_code, command_json, exit_kill_json, prompt, created_at, updated_at";
+
+    // foreign_keys cannot be toggled inside a transaction
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")
+        .map_err(|e| Error::Database(e.to_string()))?;
+    let result = conn.execute_batch(&format!(
+        "BEGIN;
+         CREATE TABLE processes_new ({ddl});
+         INSERT INTO processes_new ({columns}) SELECT {columns} FROM processes;
+         UPDATE processes_new SET name = project || ':' || COALESCE(branch, '');
+         DROP TABLE processes;
+         ALTER TABLE processes_new RENAME TO processes;
+         COMMIT;",
+        ddl = PROCESSES_DDL,
+        columns = columns,
+    ));
+    if result.is_err() {
+        let _ = conn.execute_batch("ROLLBACK;");
+    }
+    conn.execute_batch("PRAGMA foreign_keys = ON;")
+        .map_err(|e| Error::Database(e.to_string()))?;
+    result.map_err(|e| Error::Database(e.to_string()))
+}
+
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let exists: bool = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1", table),
+            params![column],
+            |row| {
+                let count: i64 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))
+            .map_err(|e| Error::Database(e.to_string()))?;
+    }
     Ok(())
 }
 
@@ -245,7 +589,7 @@ fn migrate_state_json(conn: &Connection, path: &std::path::Path) -> Result<()> {
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 entry.id.to_string(),
-                entry.branch,          // use branch as name for migration
+                format!("{}:{}", entry.project, entry.branch),
                 entry.project,
                 entry.path.to_string_lossy().to_string(),
                 entry.branch,
@@ -353,7 +697,13 @@ fn migrate_tools_db(conn: &Connection, tools_path: &std::path::Path) -> Result<(
 // Process CRUD
 // ---------------------------------------------------------------------------
 
-use crate::state::{Command, Entry, ExitKill};
+use crate::state::{Command, Entry, ExitKill, ProcessStatus};
+
+/// Column list shared by every query that is decoded with `row_to_entry`.
+const PROCESS_COLUMNS: &str = "id, project, directory, branch, tmux_session, tmux_window,
+     command_json, exit_kill_json, exit_code, created_at, pid, status, updated_at,
+     repo_path, git_common_dir, removed_at, run_count, blocked_on, remote, pushed_sha, pushed_at,
+     base, created_branch";
 
 pub fn insert_process(conn: &Connection, entry: &Entry) -> Result<()> {
     let now = Utc::now().to_rfc3339();
@@ -366,44 +716,95 @@ pub fn insert_process(conn: &Connection, entry: &Entry) -> Result<()> {
 
     let prompt = match &entry.command {
         Command::Claude { prompt, .. } => Some(prompt.clone()),
-        Command::Raw { .. } => None,
+        Command::Raw { .. } | Command::External => None,
     };
 
     conn.execute(
         "INSERT INTO processes
             (id, name, project, directory, branch, tmux_session, tmux_window,
-             status, exit_code, command_json, exit_kill_json, prompt,
-             created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+             pid, status, exit_code, command_json, exit_kill_json, prompt,
+             created_at, updated_at, repo_path, git_common_dir, remote, base, created_branch)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                 ?19, ?20)",
         params![
             entry.id.to_string(),
-            entry.branch,  // use branch as name
+            format!("{}:{}", entry.project, entry.branch),
             entry.project,
             entry.path.to_string_lossy().to_string(),
             entry.branch,
             entry.tmux_session,
             entry.tmux_window,
-            "spawned",
+            entry.pid,
+            entry.status.as_str(),
             entry.exit_code,
             command_json,
             exit_kill_json,
             prompt,
             entry.created_at.to_rfc3339(),
             now,
+            entry.repo_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            entry.git_common_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+            entry.remote,
+            entry.base,
+            entry.created_branch,
         ],
     )
     .map_err(|e| Error::Database(e.to_string()))?;
     Ok(())
 }
 
+/// Soft-delete: the entry disappears from listings and lookups by branch, but
+/// can still be found for `wortex logs`.
+pub fn archive_process(conn: &Connection, id: Uuid) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE processes SET removed_at = ?1, updated_at = ?1 WHERE id = ?2",
+        params![now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Archived entries, oldest first.
+pub fn get_archived_processes(conn: &Connection) -> Result<Vec<Entry>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM processes WHERE removed_at IS NOT NULL ORDER BY created_at ASC",
+            PROCESS_COLUMNS
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], row_to_entry)
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    }
+    Ok(entries)
+}
+
 pub fn delete_process(conn: &Connection, id: Uuid) -> Result<()> {
-    // Delete associated tool calls first (FK constraint)
+    // Delete associated tool calls, runs and events first (FK constraint)
     conn.execute(
         "DELETE FROM tool_calls WHERE process_id = ?1",
         params![id.to_string()],
     )
     .map_err(|e| Error::Database(e.to_string()))?;
 
+    conn.execute(
+        "DELETE FROM events WHERE process_id = ?1",
+        params![id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+
+    conn.execute(
+        "DELETE FROM runs WHERE process_id = ?1",
+        params![id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+
     conn.execute(
         "DELETE FROM processes WHERE id = ?1",
         params![id.to_string()],
@@ -412,11 +813,105 @@ pub fn delete_process(conn: &Connection, id: Uuid) -> Result<()> {
     Ok(())
 }
 
-pub fn set_exit_code(conn: &Connection, id: Uuid, code: i32) -> Result<()> {
+/// Record the child PID once `__run` has spawned it. Each call starts a new
+/// run: the entry's `run_count` is bumped and a `runs` row opened.
+pub fn set_running(conn: &Connection, id: Uuid, pid: u32) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| Error::Database(e.to_string()))?;
+    tx.execute(
+        "UPDATE processes SET pid = ?1, status = 'running', run_count = run_count + 1,
+                              updated_at = ?2
+         WHERE id = ?3",
+        params![pid, now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    tx.execute(
+        "INSERT INTO runs (process_id, run_number, pid, status, started_at)
+         SELECT id, run_count, ?1, 'running', ?2 FROM processes WHERE id = ?3",
+        params![pid, now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    tx.commit().map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record how the child terminated (exited, killed or signaled) and its exit
+/// code, on the entry and on its current run.
+pub fn set_terminated(conn: &Connection, id: Uuid, status: ProcessStatus, code: i32) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE processes SET exit_code = ?1, status = ?2, blocked_on = NULL, updated_at = ?3
+         WHERE id = ?4",
+        params![code, status.as_str(), now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    conn.execute(
+        "UPDATE runs SET exit_code = ?1, status = ?2, ended_at = ?3
+         WHERE process_id = ?4
+           AND run_number = (SELECT run_count FROM processes WHERE id = ?4)",
+        params![code, status.as_str(), now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Put an entry back to `spawned` before `__run` is started again. Earlier
+/// attempts stay in `runs`; one that never recorded its end (killed by a forced
+/// restart) is closed as `signaled`.
+pub fn reset_process(conn: &Connection, id: Uuid) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE runs SET status = 'signaled', ended_at = ?1
+         WHERE process_id = ?2 AND ended_at IS NULL",
+        params![now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    conn.execute(
+        "UPDATE processes SET status = 'spawned', pid = NULL, exit_code = NULL, blocked_on = NULL,
+                              updated_at = ?1
+         WHERE id = ?2",
+        params![now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// The agent raised a notification (permission prompt, waiting for input).
+/// Only a live entry is moved to `waiting`; a late hook must not revive one
+/// that already terminated.
+pub fn set_waiting(conn: &Connection, id: Uuid, message: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE processes SET status = 'waiting', blocked_on = ?1, updated_at = ?2
+         WHERE id = ?3 AND status IN ('running', 'waiting')",
+        params![message, now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// The agent made progress (a tool call), so it is no longer waiting.
+pub fn clear_waiting(conn: &Connection, id: Uuid) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE processes SET status = 'running', blocked_on = NULL, updated_at = ?1
+         WHERE id = ?2 AND status = 'waiting'",
+        params![now, id.to_string()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record a successful `wortex push`: the remote it went to and the commit
+/// the remote branch now points at.
+pub fn set_pushed(conn: &Connection, id: Uuid, remote: &str, sha: &str) -> Result<()> {
     let now = Utc::now().to_rfc3339();
     conn.execute(
-        "UPDATE processes SET exit_code = ?1, status = 'exited', updated_at = ?2 WHERE id = ?3",
-        params![code, now, id.to_string()],
+        "UPDATE processes SET remote = ?1, pushed_sha = ?2, pushed_at = ?3, updated_at = ?3
+         WHERE id = ?4",
+        params![remote, sha, now, id.to_string()],
     )
     .map_err(|e| Error::Database(e.to_string()))?;
     Ok(())
@@ -424,12 +919,10 @@ pub fn set_exit_code(conn: &Connection, id: Uuid, code: i32) -> Result<()> {
 
 pub fn get_all_processes(conn: &Connection) -> Result<Vec<Entry>> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project, directory, branch, tmux_session, tmux_window,
-                    command_json, exit_kill_json, exit_code, created_at
-             FROM processes
-             ORDER BY created_at ASC",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM processes WHERE removed_at IS NULL ORDER BY created_at ASC",
+            PROCESS_COLUMNS
+        ))
         .map_err(|e| Error::Database(e.to_string()))?;
 
     let rows = stmt
@@ -445,12 +938,7 @@ pub fn get_all_processes(conn: &Connection) -> Result<Vec<Entry>> {
 
 pub fn get_process_by_id(conn: &Connection, id: Uuid) -> Result<Option<Entry>> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project, directory, branch, tmux_session, tmux_window,
-                    command_json, exit_kill_json, exit_code, created_at
-             FROM processes
-             WHERE id = ?1",
-        )
+        .prepare(&format!("SELECT {} FROM processes WHERE id = ?1", PROCESS_COLUMNS))
         .map_err(|e| Error::Database(e.to_string()))?;
 
     let mut rows = stmt
@@ -463,18 +951,57 @@ pub fn get_process_by_id(conn: &Connection, id: Uuid) -> Result<Option<Entry>> {
     }
 }
 
-pub fn get_process_by_branch(conn: &Connection, branch: &str) -> Result<Option<Entry>> {
+/// Entries for a branch across all projects, live ones first, then newest
+/// first. Removed entries are only included with `include_removed`.
+pub fn get_processes_by_branch(
+    conn: &Connection,
+    branch: &str,
+    include_removed: bool,
+) -> Result<Vec<Entry>> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project, directory, branch, tmux_session, tmux_window,
-                    command_json, exit_kill_json, exit_code, created_at
-             FROM processes
-             WHERE branch = ?1",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM processes WHERE branch = ?1 AND (?2 OR removed_at IS NULL)
+             ORDER BY removed_at IS NULL DESC, created_at DESC",
+            PROCESS_COLUMNS
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(params![branch, include_removed], row_to_entry)
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    }
+    Ok(entries)
+}
+
+/// The live entry for `branch` in the repo whose shared git directory is
+/// `git_common_dir`. Rows recorded before that column existed are keyed by
+/// their project prefix, as in `idx_processes_active_repo_branch`, so a
+/// legacy row with the repo's `project` matches too.
+pub fn get_process_in_repo(
+    conn: &Connection,
+    git_common_dir: &Path,
+    project: &str,
+    branch: &str,
+) -> Result<Option<Entry>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM processes
+             WHERE branch = ?3 AND removed_at IS NULL
+               AND (git_common_dir = ?1 OR (git_common_dir IS NULL AND project = ?2))
+             ORDER BY git_common_dir IS NULL",
+            PROCESS_COLUMNS
+        ))
         .map_err(|e| Error::Database(e.to_string()))?;
 
     let mut rows = stmt
-        .query_map(params![branch], row_to_entry)
+        .query_map(
+            params![git_common_dir.to_string_lossy().to_string(), project, branch],
+            row_to_entry,
+        )
         .map_err(|e| Error::Database(e.to_string()))?;
 
     match rows.next() {
@@ -494,6 +1021,19 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<Entry> {
     let exit_kill_json: Option<String> = row.get(7)?;
     let exit_code: Option<i32> = row.get(8)?;
     let created_at_str: String = row.get(9)?;
+    let pid: Option<u32> = row.get(10)?;
+    let status_str: String = row.get(11)?;
+    let updated_at_str: String = row.get(12)?;
+    let repo_path: Option<String> = row.get(13)?;
+    let git_common_dir: Option<String> = row.get(14)?;
+    let removed_at_str: Option<String> = row.get(15)?;
+    let run_count: u32 = row.get(16)?;
+    let blocked_on: Option<String> = row.get(17)?;
+    let remote: Option<String> = row.get(18)?;
+    let pushed_sha: Option<String> = row.get(19)?;
+    let pushed_at_str: Option<String> = row.get(20)?;
+    let base: Option<String> = row.get(21)?;
+    let created_branch: bool = row.get(22)?;
 
     let id = Uuid::parse_str(&id_str).unwrap_or_default();
     let command: Command = serde_json::from_str(&command_json).unwrap_or(Command::Raw {
@@ -504,6 +1044,16 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<Entry> {
     let created_at = DateTime::parse_from_rfc3339(&created_at_str)
         .map(|dt| dt.with_timezone(&Utc))
         .unwrap_or_default();
+    let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(created_at);
+    let status = ProcessStatus::parse(&status_str).unwrap_or(ProcessStatus::Spawned);
+    let removed_at = removed_at_str
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+    let pushed_at = pushed_at_str
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc));
 
     Ok(Entry {
         id,
@@ -515,7 +1065,20 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<Entry> {
         command,
         exit_kill,
         exit_code,
+        status,
+        pid,
+        blocked_on,
+        repo_path: repo_path.map(PathBuf::from),
+        git_common_dir: git_common_dir.map(PathBuf::from),
+        remote,
+        base,
+        created_branch,
+        removed_at,
+        run_count,
+        pushed_sha,
+        pushed_at,
         created_at,
+        updated_at,
     })
 }
 
@@ -523,9 +1086,17 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<Entry> {
 // Tool call CRUD
 // ---------------------------------------------------------------------------
 
+/// Responses larger than this are truncated before storage.
+pub const MAX_RESPONSE_CHARS: usize = 2000;
+
+/// Column list shared by every query that is decoded with `row_to_tool_call`.
+const TOOL_CALL_COLUMNS: &str = "id, process_id, tool_name, tool_input, hook_type, timestamp, sequence,
+     tool_use_id, ended_at, duration_ms, is_error, tool_response, decision, decision_reason";
+
 fn row_to_tool_call(row: &rusqlite::Row) -> rusqlite::Result<ToolCall> {
     let process_id_str: String = row.get(1)?;
     let timestamp_str: String = row.get(5)?;
+    let ended_at_str: Option<String> = row.get(8)?;
     Ok(ToolCall {
         id: row.get(0)?,
         process_id: Uuid::parse_str(&process_id_str).unwrap_or_default(),
@@ -536,16 +1107,110 @@ fn row_to_tool_call(row: &rusqlite::Row) -> rusqlite::Result<ToolCall> {
             .map(|dt| dt.with_timezone(&Utc))
             .unwrap_or_default(),
         sequence: row.get(6)?,
+        tool_use_id: row.get(7)?,
+        ended_at: ended_at_str
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        duration_ms: row.get(9)?,
+        is_error: row.get(10)?,
+        tool_response: row.get(11)?,
+        decision: row.get(12)?,
+        decision_reason: row.get(13)?,
     })
 }
 
+/// Record a PostToolUse hook.
+///
+/// When a pending call with the same `tool_use_id` exists it is completed in
+/// place (end time, duration, error flag and response). Otherwise a standalone
+/// "post" row is inserted so the result is not lost.
+pub fn complete_tool_call(
+    conn: &Connection,
+    process_id: Uuid,
+    tool_use_id: Option<&str>,
+    tool_name: &str,
+    input: &str,
+    response: Option<&str>,
+    is_error: bool,
+) -> Result<()> {
+    let ended_at = Utc::now();
+    let response = response.map(|r| truncate_chars(r, MAX_RESPONSE_CHARS));
+
+    let pending: Option<(i64, String)> = match tool_use_id {
+        Some(tool_use_id) => conn
+            .query_row(
+                "SELECT id, timestamp FROM tool_calls
+                 WHERE process_id = ?1 AND tool_use_id = ?2 AND ended_at IS NULL
+                 ORDER BY id DESC LIMIT 1",
+                params![process_id.to_string(), tool_use_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| Error::Database(e.to_string()))?,
+        None => None,
+    };
+
+    let (row_id, duration_ms) = match pending {
+        Some((row_id, started_at)) => {
+            let duration_ms = DateTime::parse_from_rfc3339(&started_at)
+                .map(|dt| (ended_at - dt.with_timezone(&Utc)).num_milliseconds().max(0))
+                .ok();
+            conn.execute(
+                "UPDATE tool_calls SET hook_type = 'post' WHERE id = ?1",
+                params![row_id],
+            )
+            .map_err(|e| Error::Database(e.to_string()))?;
+            (row_id, duration_ms)
+        }
+        None => {
+            let row_id =
+                insert_tool_call(conn, process_id, "post", tool_name, input, tool_use_id)?;
+            (row_id, None)
+        }
+    };
+
+    conn.execute(
+        "UPDATE tool_calls
+         SET ended_at = ?1, duration_ms = ?2, is_error = ?3, tool_response = ?4
+         WHERE id = ?5",
+        params![ended_at.to_rfc3339(), duration_ms, is_error, response, row_id],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record the policy decision made for a PreToolUse row.
+pub fn set_tool_call_decision(
+    conn: &Connection,
+    row_id: i64,
+    decision: &str,
+    reason: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE tool_calls SET decision = ?1, decision_reason = ?2 WHERE id = ?3",
+        params![decision, reason, row_id],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(())
+}
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => s[..idx].to_string(),
+        None => s.to_string(),
+    }
+}
+
+/// Insert a tool call row and return its id. PreToolUse rows are completed
+/// later by `complete_tool_call`.
 pub fn insert_tool_call(
     conn: &Connection,
     process_id: Uuid,
     hook_type: &str,
     tool_name: &str,
     input: &str,
-) -> Result<()> {
+    tool_use_id: Option<&str>,
+) -> Result<i64> {
     let timestamp = Utc::now().to_rfc3339();
 
     // Get next sequence number for this process
@@ -558,33 +1223,44 @@ pub fn insert_tool_call(
         .map_err(|e| Error::Database(e.to_string()))?;
 
     conn.execute(
-        "INSERT INTO tool_calls (process_id, tool_name, tool_input, hook_type, timestamp, sequence)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO tool_calls
+            (process_id, tool_name, tool_input, hook_type, timestamp, sequence, tool_use_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![
             process_id.to_string(),
             tool_name,
             input,
             hook_type,
             timestamp,
-            next_seq
+            next_seq,
+            tool_use_id
         ],
     )
     .map_err(|e| Error::Database(e.to_string()))?;
-    Ok(())
+    Ok(conn.last_insert_rowid())
 }
 
-pub fn get_tool_calls_by_process(conn: &Connection, process_id: Uuid) -> Result<Vec<ToolCall>> {
+/// Tool calls matching `filter`, oldest first. With a limit, only the most
+/// recent `limit` calls are returned.
+pub fn get_tool_calls(
+    conn: &Connection,
+    filter: &ToolCallFilter,
+    limit: Option<usize>,
+) -> Result<Vec<ToolCall>> {
+    let (condition, mut args) = filter.to_sql();
+    // SQLite treats a negative limit as no limit
+    args.push(Value::Integer(limit.map_or(-1, |n| n as i64)));
     let mut stmt = conn
-        .prepare(
-            "SELECT id, process_id, tool_name, tool_input, hook_type, timestamp, sequence
-             FROM tool_calls
-             WHERE process_id = ?1
-             ORDER BY sequence ASC",
-        )
+        .prepare(&format!(
+            "SELECT * FROM (
+                SELECT {} FROM tool_calls WHERE {} ORDER BY id DESC LIMIT ?
+             ) ORDER BY id ASC",
+            TOOL_CALL_COLUMNS, condition
+        ))
         .map_err(|e| Error::Database(e.to_string()))?;
 
     let rows = stmt
-        .query_map(params![process_id.to_string()], row_to_tool_call)
+        .query_map(rusqlite::params_from_iter(&args), row_to_tool_call)
         .map_err(|e| Error::Database(e.to_string()))?;
 
     let mut calls = Vec::new();
@@ -594,24 +1270,333 @@ pub fn get_tool_calls_by_process(conn: &Connection, process_id: Uuid) -> Result<
     Ok(calls)
 }
 
-pub fn get_all_tool_calls(conn: &Connection) -> Result<Vec<ToolCall>> {
+/// Call `f` with each tool call matching `filter`, oldest first, without
+/// loading them all into memory.
+pub fn for_each_tool_call<F>(conn: &Connection, filter: &ToolCallFilter, mut f: F) -> Result<()>
+where
+    F: FnMut(ToolCall) -> Result<()>,
+{
+    let (condition, args) = filter.to_sql();
     let mut stmt = conn
-        .prepare(
-            "SELECT id, process_id, tool_name, tool_input, hook_type, timestamp, sequence
-             FROM tool_calls
-             ORDER BY timestamp DESC",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM tool_calls WHERE {} ORDER BY id ASC",
+            TOOL_CALL_COLUMNS, condition
+        ))
         .map_err(|e| Error::Database(e.to_string()))?;
 
-    let rows = stmt
-        .query_map([], row_to_tool_call)
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(&args))
         .map_err(|e| Error::Database(e.to_string()))?;
-
-    let mut calls = Vec::new();
-    for row in rows {
-        calls.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    while let Some(row) = rows.next().map_err(|e| Error::Database(e.to_string()))? {
+        f(row_to_tool_call(row).map_err(|e| Error::Database(e.to_string()))?)?;
     }
-    Ok(calls)
+    Ok(())
+}
+
+/// Sessions with tool calls matching `filter`, in order of their first call.
+pub fn tool_call_sessions(conn: &Connection, filter: &ToolCallFilter) -> Result<Vec<Uuid>> {
+    let (condition, args) = filter.to_sql();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT process_id FROM tool_calls WHERE {}
+             GROUP BY process_id ORDER BY MIN(id)",
+            condition
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(&args), |row| row.get::<_, String>(0))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut ids = Vec::new();
+    for row in rows {
+        let id = row.map_err(|e| Error::Database(e.to_string()))?;
+        ids.push(Uuid::parse_str(&id).unwrap_or_default());
+    }
+    Ok(ids)
+}
+
+/// Id of the newest tool call, 0 if there are none.
+pub fn last_tool_call_id(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COALESCE(MAX(id), 0) FROM tool_calls", [], |row| {
+        row.get(0)
+    })
+    .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Tool calls matching `filter` inserted after the row `after_id`, oldest first.
+pub fn get_tool_calls_after(
+    conn: &Connection,
+    filter: &ToolCallFilter,
+    after_id: i64,
+) -> Result<Vec<ToolCall>> {
+    let (condition, mut args) = filter.to_sql();
+    args.insert(0, Value::Integer(after_id));
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM tool_calls WHERE id > ? AND {} ORDER BY id ASC",
+            TOOL_CALL_COLUMNS, condition
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(&args), row_to_tool_call)
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut calls = Vec::new();
+    for row in rows {
+        calls.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    }
+    Ok(calls)
+}
+
+// ---------------------------------------------------------------------------
+// Tool call statistics
+// ---------------------------------------------------------------------------
+
+/// Bash commands listed in `ToolStats::top_commands`.
+pub const TOP_COMMANDS: usize = 10;
+
+/// A string field of `tool_input`, NULL when the input is not valid JSON.
+fn input_field(field: &str) -> String {
+    format!(
+        "CASE WHEN json_valid(tool_input) THEN json_extract(tool_input, '$.{}') END",
+        field
+    )
+}
+
+/// Aggregate the tool calls matching `filter`.
+pub fn tool_stats(conn: &Connection, filter: &ToolCallFilter) -> Result<ToolStats> {
+    let (filter, args) = filter.to_sql();
+    let db_err = |e: rusqlite::Error| Error::Database(e.to_string());
+
+    let (calls, failed, sessions, first_at, last_at): (u64, u64, u64, Option<String>, Option<String>) =
+        conn.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(is_error = 1), 0), COUNT(DISTINCT process_id),
+                        MIN(timestamp), MAX(COALESCE(ended_at, timestamp))
+                 FROM tool_calls WHERE {}",
+                filter
+            ),
+            rusqlite::params_from_iter(&args),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .map_err(db_err)?;
+    let parse = |s: Option<String>| {
+        s.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+    let first_at = parse(first_at);
+    let last_at = parse(last_at);
+    let calls_per_minute = match (first_at, last_at) {
+        (Some(first), Some(last)) => {
+            let minutes = (last - first).num_seconds().max(60) as f64 / 60.0;
+            Some(calls as f64 / minutes)
+        }
+        _ => None,
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT tool_name, COUNT(*), COALESCE(SUM(is_error = 1), 0), AVG(duration_ms)
+             FROM tool_calls WHERE {}
+             GROUP BY tool_name ORDER BY COUNT(*) DESC, tool_name",
+            filter
+        ))
+        .map_err(db_err)?;
+    let tools = stmt
+        .query_map(rusqlite::params_from_iter(&args), |row| {
+            Ok(ToolCount {
+                tool_name: row.get(0)?,
+                calls: row.get(1)?,
+                failed: row.get(2)?,
+                avg_duration_ms: row.get(3)?,
+            })
+        })
+        .map_err(db_err)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(db_err)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT path, SUM(tool_name = 'Read'), SUM(tool_name IN ('Edit', 'MultiEdit')),
+                    SUM(tool_name = 'Write')
+             FROM (SELECT tool_name, {} AS path FROM tool_calls
+                   WHERE {} AND tool_name IN ('Read', 'Edit', 'MultiEdit', 'Write')
+                         AND (decision IS NULL OR decision <> 'deny'))
+             WHERE typeof(path) = 'text'
+             GROUP BY path ORDER BY COUNT(*) DESC, path",
+            input_field("file_path"),
+            filter
+        ))
+        .map_err(db_err)?;
+    let files = stmt
+        .query_map(rusqlite::params_from_iter(&args), |row| {
+            Ok(FileAccess {
+                path: row.get(0)?,
+                read: row.get(1)?,
+                edited: row.get(2)?,
+                written: row.get(3)?,
+            })
+        })
+        .map_err(db_err)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(db_err)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT command, COUNT(*)
+             FROM (SELECT {} AS command FROM tool_calls WHERE {} AND tool_name = 'Bash')
+             WHERE typeof(command) = 'text'
+             GROUP BY command ORDER BY COUNT(*) DESC, command LIMIT {}",
+            input_field("command"),
+            filter,
+            TOP_COMMANDS
+        ))
+        .map_err(db_err)?;
+    let top_commands = stmt
+        .query_map(rusqlite::params_from_iter(&args), |row| {
+            Ok(CommandCount {
+                command: row.get(0)?,
+                calls: row.get(1)?,
+            })
+        })
+        .map_err(db_err)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(db_err)?;
+
+    Ok(ToolStats {
+        calls,
+        failed,
+        sessions,
+        first_at,
+        last_at,
+        calls_per_minute,
+        tools,
+        files,
+        top_commands,
+    })
+}
+
+/// Read, Edit/MultiEdit and Write calls the policy did not deny, with their
+/// `file_path` as `file`, joined with their session as `p`. Selects
+/// `tool_name`, `timestamp`, `process_id`, `repo` (as in the active
+/// repo/branch index), `project`, `branch` and `path`: the file relative to
+/// the session's worktree when it lies inside it.
+fn file_calls_sql(condition: &str) -> String {
+    format!(
+        "SELECT tool_name, timestamp, process_id, repo, project, branch,
+                CASE WHEN substr(file, 1, length(directory) + 1) = directory || '/'
+                     THEN substr(file, length(directory) + 2) ELSE file END AS path
+         FROM (SELECT t.tool_name, t.timestamp, t.process_id,
+                      COALESCE(p.git_common_dir, p.project) AS repo, p.project, p.branch,
+                      p.directory, {} AS file
+               FROM tool_calls t JOIN processes p ON p.id = t.process_id
+               WHERE {} AND t.tool_name IN ('Read', 'Edit', 'MultiEdit', 'Write')
+                     AND (t.decision IS NULL OR t.decision <> 'deny'))
+         WHERE typeof(file) = 'text'",
+        input_field("file_path"),
+        condition
+    )
+}
+
+/// Files a session passed to Read, Edit/MultiEdit and Write, relative to its
+/// worktree: modified files first, then files it only read.
+pub fn file_access(conn: &Connection, process_id: Uuid) -> Result<Vec<FileAccess>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT path, SUM(tool_name = 'Read'), SUM(tool_name IN ('Edit', 'MultiEdit')),
+                    SUM(tool_name = 'Write')
+             FROM ({})
+             GROUP BY path ORDER BY SUM(tool_name <> 'Read') = 0, path",
+            file_calls_sql("t.process_id = ?1")
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(params![process_id.to_string()], |row| {
+            Ok(FileAccess {
+                path: row.get(0)?,
+                read: row.get(1)?,
+                edited: row.get(2)?,
+                written: row.get(3)?,
+            })
+        })
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut files = Vec::new();
+    for row in rows {
+        files.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    }
+    Ok(files)
+}
+
+/// Files modified by two or more live sessions (spawned, running or waiting,
+/// not archived) of the same repo, counting only edits made at or after
+/// `since` when given. Ordered by project, repo, path and branch.
+pub fn shared_edits(conn: &Connection, since: Option<DateTime<Utc>>) -> Result<Vec<SharedEdit>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "WITH edits AS ({})
+             SELECT repo, project, path, process_id, branch, COUNT(*), MAX(timestamp)
+             FROM edits
+             WHERE (repo, path) IN (
+                 SELECT repo, path FROM edits
+                 GROUP BY repo, path HAVING COUNT(DISTINCT process_id) > 1)
+             GROUP BY repo, path, process_id
+             ORDER BY project, repo, path, branch",
+            file_calls_sql(
+                "p.removed_at IS NULL AND p.status IN ('spawned', 'running', 'waiting')
+                 AND t.tool_name <> 'Read' AND (?1 IS NULL OR t.timestamp >= ?1)"
+            )
+        ))
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    // Timestamps are stored as RFC 3339 in UTC, which sorts as text
+    let since = since.map(|t| t.to_rfc3339());
+    let rows = stmt
+        .query_map(params![since], |row| {
+            let process_id: String = row.get(3)?;
+            let last_edit_at: Option<String> = row.get(6)?;
+            Ok(SharedEdit {
+                repo: row.get(0)?,
+                project: row.get(1)?,
+                path: row.get(2)?,
+                process_id: Uuid::parse_str(&process_id).unwrap_or_default(),
+                branch: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                edits: row.get(5)?,
+                last_edit_at: last_edit_at
+                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+            })
+        })
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut edits = Vec::new();
+    for row in rows {
+        edits.push(row.map_err(|e| Error::Database(e.to_string()))?);
+    }
+    Ok(edits)
+}
+
+// ---------------------------------------------------------------------------
+// Event CRUD
+// ---------------------------------------------------------------------------
+
+/// Record something that happened to an entry outside its tool calls, e.g.
+/// input sent to its pane with `wortex send`.
+pub fn insert_event(
+    conn: &Connection,
+    process_id: Uuid,
+    kind: &str,
+    detail: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO events (process_id, kind, detail, timestamp) VALUES (?1, ?2, ?3, ?4)",
+        params![process_id.to_string(), kind, detail, Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(conn.last_insert_rowid())
 }
 
 // ---------------------------------------------------------------------------
@@ -621,7 +1606,7 @@ pub fn get_all_tool_calls(conn: &Connection) -> Result<Vec<ToolCall>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{Command, Entry, ExitKill};
+    use crate::state::{Command, Entry, ExitKill, ProcessStatus};
     use chrono::Utc;
     use std::path::PathBuf;
 
@@ -632,21 +1617,20 @@ mod tests {
         conn
     }
 
+    fn for_process(process_id: Uuid) -> ToolCallFilter {
+        ToolCallFilter {
+            process_id: Some(process_id),
+            ..Default::default()
+        }
+    }
+
     fn make_entry(branch: &str) -> Entry {
         Entry {
-            id: Uuid::new_v4(),
-            project: "tp".to_string(),
-            branch: branch.to_string(),
-            path: PathBuf::from(format!("/tmp/tp-{}", branch)),
-            tmux_session: "dev".to_string(),
-            tmux_window: branch.to_string(),
             command: Command::Claude {
                 prompt: "do work".to_string(),
                 agent: None,
             },
-            exit_kill: None,
-            exit_code: None,
-            created_at: Utc::now(),
+            ..Entry::for_test("tp", branch)
         }
     }
 
@@ -691,6 +1675,55 @@ mod tests {
         assert_eq!(found.id, entry.id);
         assert_eq!(found.branch, "feat-a");
         assert_eq!(found.project, "tp");
+        assert!(found.created_branch);
+
+        // Checked out with `--existing`
+        let existing = Entry {
+            created_branch: false,
+            ..make_entry("feat-e")
+        };
+        insert_process(&conn, &existing).unwrap();
+        assert!(!get_process_by_id(&conn, existing.id).unwrap().unwrap().created_branch);
+    }
+
+    #[test]
+    fn test_process_repo_paths_round_trip() {
+        let conn = test_conn();
+        let mut entry = make_entry("feat-r");
+        entry.repo_path = Some(PathBuf::from("/repos/tp"));
+        entry.git_common_dir = Some(PathBuf::from("/repos/tp/.git"));
+        insert_process(&conn, &entry).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.repo_path, Some(PathBuf::from("/repos/tp")));
+        assert_eq!(found.git_common_dir, Some(PathBuf::from("/repos/tp/.git")));
+
+        // Entries from older versions have neither
+        let legacy = make_entry("feat-l");
+        insert_process(&conn, &legacy).unwrap();
+        let found = get_process_by_id(&conn, legacy.id).unwrap().unwrap();
+        assert_eq!(found.repo_path, None);
+    }
+
+    #[test]
+    fn test_set_pushed() {
+        let conn = test_conn();
+        let mut entry = make_entry("feat-p");
+        entry.remote = Some("origin".to_string());
+        entry.base = Some("develop".to_string());
+        insert_process(&conn, &entry).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.remote.as_deref(), Some("origin"));
+        assert_eq!(found.base.as_deref(), Some("develop"));
+        assert_eq!(found.pushed_sha, None);
+        assert_eq!(found.pushed_at, None);
+
+        set_pushed(&conn, entry.id, "fork", "abc123").unwrap();
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.remote.as_deref(), Some("fork"));
+        assert_eq!(found.pushed_sha.as_deref(), Some("abc123"));
+        assert!(found.pushed_at.is_some());
     }
 
     #[test]
@@ -699,12 +1732,152 @@ mod tests {
         let entry = make_entry("feat-b");
         insert_process(&conn, &entry).unwrap();
 
-        let found = get_process_by_branch(&conn, "feat-b").unwrap();
-        assert!(found.is_some());
+        let found = get_processes_by_branch(&conn, "feat-b", false).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, entry.id);
+
+        assert!(get_processes_by_branch(&conn, "nope", false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_process_in_repo() {
+        let conn = test_conn();
+        let mut entry = make_entry("feat-b");
+        entry.git_common_dir = Some(PathBuf::from("/src/tp/.git"));
+        insert_process(&conn, &entry).unwrap();
+
+        let found = get_process_in_repo(&conn, Path::new("/src/tp/.git"), "tp", "feat-b").unwrap();
         assert_eq!(found.unwrap().id, entry.id);
+        assert!(get_process_in_repo(&conn, Path::new("/src/other/.git"), "tp", "feat-b")
+            .unwrap()
+            .is_none());
+
+        // A row from before git_common_dir was stored is found by its project
+        let legacy = make_entry("feat-l");
+        insert_process(&conn, &legacy).unwrap();
+        let found = get_process_in_repo(&conn, Path::new("/src/tp/.git"), "tp", "feat-l").unwrap();
+        assert_eq!(found.unwrap().id, legacy.id);
+        assert!(get_process_in_repo(&conn, Path::new("/src/tp/.git"), "other", "feat-l")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_same_branch_in_two_projects() {
+        let conn = test_conn();
+        let a = make_entry("fix-ci");
+        let mut b = make_entry("fix-ci");
+        b.project = "other".to_string();
+        insert_process(&conn, &a).unwrap();
+        insert_process(&conn, &b).unwrap();
+
+        assert_eq!(get_processes_by_branch(&conn, "fix-ci", false).unwrap().len(), 2);
+
+        // Without a recorded repo, same project and branch is still rejected
+        assert!(insert_process(&conn, &make_entry("fix-ci")).is_err());
+    }
+
+    #[test]
+    fn test_same_prefix_in_two_repos() {
+        // my-project and mega-proxy both derive "mp"
+        let conn = test_conn();
+        let mut a = make_entry("fix-ci");
+        a.git_common_dir = Some(PathBuf::from("/src/my-project/.git"));
+        let mut b = make_entry("fix-ci");
+        b.git_common_dir = Some(PathBuf::from("/src/mega-proxy/.git"));
+        insert_process(&conn, &a).unwrap();
+        insert_process(&conn, &b).unwrap();
+
+        let in_repo = |dir: &str| {
+            get_process_in_repo(&conn, Path::new(dir), "mp", "fix-ci")
+                .unwrap()
+                .unwrap()
+                .id
+        };
+        assert_eq!(in_repo("/src/my-project/.git"), a.id);
+        assert_eq!(in_repo("/src/mega-proxy/.git"), b.id);
+
+        // Same repo and branch is rejected
+        let mut c = make_entry("fix-ci");
+        c.git_common_dir = a.git_common_dir.clone();
+        assert!(insert_process(&conn, &c).is_err());
+    }
+
+    #[test]
+    fn test_archive_process() {
+        let conn = test_conn();
+        let mut old = make_entry("fix-ci");
+        old.git_common_dir = Some(PathBuf::from("/src/tp/.git"));
+        insert_process(&conn, &old).unwrap();
+        archive_process(&conn, old.id).unwrap();
+
+        // Hidden from live lookups, but kept
+        assert!(get_all_processes(&conn).unwrap().is_empty());
+        assert!(get_process_in_repo(&conn, Path::new("/src/tp/.git"), "tp", "fix-ci")
+            .unwrap()
+            .is_none());
+        assert!(get_processes_by_branch(&conn, "fix-ci", false).unwrap().is_empty());
+        let found = get_process_by_id(&conn, old.id).unwrap().unwrap();
+        assert!(found.removed_at.is_some());
+
+        // The branch can be started again; the live entry is preferred
+        let new = make_entry("fix-ci");
+        insert_process(&conn, &new).unwrap();
+        let all = get_processes_by_branch(&conn, "fix-ci", true).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, new.id);
+        assert_eq!(get_archived_processes(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_runs_history() {
+        let conn = test_conn();
+        let entry = make_entry("retry");
+        insert_process(&conn, &entry).unwrap();
+
+        set_running(&conn, entry.id, 100).unwrap();
+        set_terminated(&conn, entry.id, ProcessStatus::Exited, 1).unwrap();
+        reset_process(&conn, entry.id).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Spawned);
+        assert_eq!(found.exit_code, None);
+        assert_eq!(found.pid, None);
+        assert_eq!(found.run_count, 1);
+
+        set_running(&conn, entry.id, 200).unwrap();
+        set_terminated(&conn, entry.id, ProcessStatus::Exited, 0).unwrap();
+
+        // A run cut short by a forced restart is closed
+        set_running(&conn, entry.id, 300).unwrap();
+        reset_process(&conn, entry.id).unwrap();
+        let status: String = conn
+            .query_row("SELECT status FROM runs WHERE run_number = 3", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(status, "signaled");
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.run_count, 3);
 
-        let missing = get_process_by_branch(&conn, "nope").unwrap();
-        assert!(missing.is_none());
+        let mut stmt = conn
+            .prepare(
+                "SELECT run_number, pid, exit_code FROM runs
+                 WHERE process_id = ?1 AND exit_code IS NOT NULL ORDER BY run_number",
+            )
+            .unwrap();
+        let runs: Vec<(u32, u32, i32)> = stmt
+            .query_map(params![entry.id.to_string()], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(runs, vec![(1, 100, 1), (2, 200, 0)]);
+
+        // Runs go with the entry
+        delete_process(&conn, entry.id).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM runs", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
     }
 
     #[test]
@@ -734,10 +1907,86 @@ mod tests {
         let entry = make_entry("exiting");
         insert_process(&conn, &entry).unwrap();
 
-        set_exit_code(&conn, entry.id, 42).unwrap();
+        set_terminated(&conn, entry.id, ProcessStatus::Exited, 42).unwrap();
 
         let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
         assert_eq!(found.exit_code, Some(42));
+        assert_eq!(found.status, ProcessStatus::Exited);
+    }
+
+    #[test]
+    fn test_new_process_is_spawned_without_pid() {
+        let conn = test_conn();
+        let entry = make_entry("fresh");
+        insert_process(&conn, &entry).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Spawned);
+        assert_eq!(found.pid, None);
+    }
+
+    #[test]
+    fn test_set_running_records_pid() {
+        let conn = test_conn();
+        let entry = make_entry("running");
+        insert_process(&conn, &entry).unwrap();
+
+        set_running(&conn, entry.id, 4242).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Running);
+        assert_eq!(found.pid, Some(4242));
+        assert_eq!(found.exit_code, None);
+    }
+
+    #[test]
+    fn test_set_terminated_by_signal() {
+        let conn = test_conn();
+        let entry = make_entry("signaled");
+        insert_process(&conn, &entry).unwrap();
+        set_running(&conn, entry.id, 4242).unwrap();
+
+        set_terminated(&conn, entry.id, ProcessStatus::Signaled, 143).unwrap();
+
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Signaled);
+        assert_eq!(found.exit_code, Some(143));
+        assert_eq!(found.pid, Some(4242));
+        assert!(found.updated_at >= found.created_at);
+    }
+
+    #[test]
+    fn test_waiting_set_and_cleared() {
+        let conn = test_conn();
+        let entry = make_entry("perm");
+        insert_process(&conn, &entry).unwrap();
+
+        // Not started yet: a notification does not change the status
+        set_waiting(&conn, entry.id, "Claude needs your permission to use Bash").unwrap();
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Spawned);
+
+        set_running(&conn, entry.id, 42).unwrap();
+        set_waiting(&conn, entry.id, "Claude needs your permission to use Bash").unwrap();
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Waiting);
+        assert_eq!(
+            found.blocked_on.as_deref(),
+            Some("Claude needs your permission to use Bash")
+        );
+
+        clear_waiting(&conn, entry.id).unwrap();
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Running);
+        assert_eq!(found.blocked_on, None);
+
+        // Exiting while waiting clears the reason; later hooks are ignored
+        set_waiting(&conn, entry.id, "waiting for input").unwrap();
+        set_terminated(&conn, entry.id, ProcessStatus::Exited, 0).unwrap();
+        set_waiting(&conn, entry.id, "waiting for input").unwrap();
+        let found = get_process_by_id(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(found.status, ProcessStatus::Exited);
+        assert_eq!(found.blocked_on, None);
     }
 
     #[test]
@@ -771,6 +2020,15 @@ mod tests {
             Command::Raw { cmd } => assert_eq!(cmd, "npm test"),
             _ => panic!("expected Raw command"),
         }
+
+        let mut external_entry = make_entry("external-test");
+        external_entry.command = Command::External;
+        external_entry.status = ProcessStatus::External;
+        insert_process(&conn, &external_entry).unwrap();
+
+        let found = get_process_by_id(&conn, external_entry.id).unwrap().unwrap();
+        assert!(matches!(found.command, Command::External));
+        assert_eq!(found.status, ProcessStatus::External);
     }
 
     #[test]
@@ -803,14 +2061,31 @@ mod tests {
         let entry = make_entry("tc-test");
         insert_process(&conn, &entry).unwrap();
 
-        insert_tool_call(&conn, entry.id, "pre", "Read", r#"{"path":"/test"}"#).unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Read", r#"{"path":"/test"}"#, None).unwrap();
 
-        let calls = get_tool_calls_by_process(&conn, entry.id).unwrap();
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].process_id, entry.id);
         assert_eq!(calls[0].hook_type, "pre");
         assert_eq!(calls[0].tool_name, "Read");
         assert_eq!(calls[0].sequence, 1);
+        assert_eq!(calls[0].decision, None);
+    }
+
+    #[test]
+    fn test_set_tool_call_decision() {
+        let conn = test_conn();
+        let entry = make_entry("decision-test");
+        insert_process(&conn, &entry).unwrap();
+
+        let row_id =
+            insert_tool_call(&conn, entry.id, "pre", "Bash", r#"{"command":"rm -rf /"}"#, None)
+                .unwrap();
+        set_tool_call_decision(&conn, row_id, "deny", Some("rm -rf is not allowed")).unwrap();
+
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
+        assert_eq!(calls[0].decision.as_deref(), Some("deny"));
+        assert_eq!(calls[0].decision_reason.as_deref(), Some("rm -rf is not allowed"));
     }
 
     #[test]
@@ -819,11 +2094,11 @@ mod tests {
         let entry = make_entry("seq-test");
         insert_process(&conn, &entry).unwrap();
 
-        insert_tool_call(&conn, entry.id, "pre", "Read", "{}").unwrap();
-        insert_tool_call(&conn, entry.id, "post", "Read", "{}").unwrap();
-        insert_tool_call(&conn, entry.id, "pre", "Write", "{}").unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, entry.id, "post", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Write", "{}", None).unwrap();
 
-        let calls = get_tool_calls_by_process(&conn, entry.id).unwrap();
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
         assert_eq!(calls.len(), 3);
         assert_eq!(calls[0].sequence, 1);
         assert_eq!(calls[1].sequence, 2);
@@ -838,12 +2113,12 @@ mod tests {
         insert_process(&conn, &e1).unwrap();
         insert_process(&conn, &e2).unwrap();
 
-        insert_tool_call(&conn, e1.id, "pre", "Read", "{}").unwrap();
-        insert_tool_call(&conn, e1.id, "pre", "Write", "{}").unwrap();
-        insert_tool_call(&conn, e2.id, "pre", "Bash", "{}").unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Write", "{}", None).unwrap();
+        insert_tool_call(&conn, e2.id, "pre", "Bash", "{}", None).unwrap();
 
-        let c1 = get_tool_calls_by_process(&conn, e1.id).unwrap();
-        let c2 = get_tool_calls_by_process(&conn, e2.id).unwrap();
+        let c1 = get_tool_calls(&conn, &for_process(e1.id), None).unwrap();
+        let c2 = get_tool_calls(&conn, &for_process(e2.id), None).unwrap();
         assert_eq!(c1.len(), 2);
         assert_eq!(c2.len(), 1);
         assert_eq!(c2[0].tool_name, "Bash");
@@ -859,24 +2134,297 @@ mod tests {
         insert_process(&conn, &e1).unwrap();
         insert_process(&conn, &e2).unwrap();
 
-        insert_tool_call(&conn, e1.id, "pre", "Read", "{}").unwrap();
-        insert_tool_call(&conn, e2.id, "pre", "Write", "{}").unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, e2.id, "pre", "Write", "{}", None).unwrap();
 
-        let all = get_all_tool_calls(&conn).unwrap();
+        let all = get_tool_calls(&conn, &ToolCallFilter::default(), None).unwrap();
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_tool_call_text_filters_match_decoded_values() {
+        let conn = test_conn();
+        let entry = make_entry("decoded");
+        insert_process(&conn, &entry).unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Bash", r#"{"command":"echo \"hi\""}"#, None)
+            .unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Edit", r#"{"edits":[{"new":"a\nb"}]}"#, None)
+            .unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Odd", "not json", None).unwrap();
+
+        let tools = |contains: Option<&str>, regex: Option<&str>| -> Vec<String> {
+            let filter = ToolCallFilter {
+                contains: contains.map(str::to_string),
+                regex: regex.map(str::to_string),
+                ..Default::default()
+            };
+            get_tool_calls(&conn, &filter, None)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.tool_name)
+                .collect()
+        };
+        assert_eq!(tools(Some(r#"echo "hi""#), None), vec!["Bash"]);
+        assert_eq!(tools(None, Some(r#"^echo "hi"$"#)), vec!["Bash"]);
+        // Nested values are searched, with escapes decoded
+        assert_eq!(tools(None, Some("^a\nb$")), vec!["Edit"]);
+        assert!(tools(Some("edits"), None).is_empty());
+        assert_eq!(tools(None, Some("^not json$")), vec!["Odd"]);
+    }
+
+    #[test]
+    fn test_tool_call_filters() {
+        let conn = test_conn();
+        let e1 = make_entry("filter-a");
+        let mut e2 = make_entry("filter-b");
+        e2.project = "other".to_string();
+        insert_process(&conn, &e1).unwrap();
+        insert_process(&conn, &e2).unwrap();
+        set_terminated(&conn, e2.id, ProcessStatus::Exited, 2).unwrap();
+
+        insert_tool_call(&conn, e1.id, "pre", "Read", r#"{"file_path":"/a.rs"}"#, None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Bash", r#"{"command":"cargo test"}"#, None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Bash", r#"{"command":"ls -la"}"#, None).unwrap();
+        insert_tool_call(&conn, e2.id, "pre", "Bash", r#"{"command":"cargo build"}"#, None).unwrap();
+
+        let count = |filter: ToolCallFilter| get_tool_calls(&conn, &filter, None).unwrap().len();
+        assert_eq!(count(ToolCallFilter::default()), 4);
+        assert_eq!(
+            count(ToolCallFilter {
+                tool_names: vec!["Read".to_string(), "Edit".to_string()],
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                contains: Some("cargo".to_string()),
+                ..Default::default()
+            }),
+            2
+        );
+        // Patterns see the decoded values, not the JSON text around them
+        assert_eq!(
+            count(ToolCallFilter {
+                regex: Some(r"^cargo (test|build)$".to_string()),
+                ..Default::default()
+            }),
+            2
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                regex: Some(r#"cargo (test|build)"}$"#.to_string()),
+                tool_names: vec!["Bash".to_string()],
+                ..Default::default()
+            }),
+            0
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                contains: Some("command".to_string()),
+                ..Default::default()
+            }),
+            0
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                project: Some("other".to_string()),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                status: Some(ProcessStatus::Exited),
+                exit_code: Some(2),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                exit_code: Some(0),
+                ..Default::default()
+            }),
+            0
+        );
+
+        let hour = chrono::Duration::hours(1);
+        assert_eq!(
+            count(ToolCallFilter {
+                since: Some(Utc::now() - hour),
+                until: Some(Utc::now() + hour),
+                ..Default::default()
+            }),
+            4
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                since: Some(Utc::now() + hour),
+                ..Default::default()
+            }),
+            0
+        );
+        assert_eq!(
+            count(ToolCallFilter {
+                until: Some(Utc::now() - hour),
+                ..Default::default()
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn test_stream_tool_calls_and_sessions() {
+        let conn = test_conn();
+        let e1 = make_entry("stream-a");
+        let e2 = make_entry("stream-b");
+        insert_process(&conn, &e1).unwrap();
+        insert_process(&conn, &e2).unwrap();
+        insert_tool_call(&conn, e2.id, "pre", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Edit", "{}", None).unwrap();
+        insert_tool_call(&conn, e2.id, "pre", "Bash", "{}", None).unwrap();
+
+        let mut names = Vec::new();
+        for_each_tool_call(&conn, &ToolCallFilter::default(), |call| {
+            names.push(call.tool_name);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(names, vec!["Read", "Edit", "Bash"]);
+
+        assert_eq!(
+            tool_call_sessions(&conn, &ToolCallFilter::default()).unwrap(),
+            vec![e2.id, e1.id]
+        );
+        assert_eq!(tool_call_sessions(&conn, &for_process(e1.id)).unwrap(), vec![e1.id]);
+    }
+
+    #[test]
+    fn test_file_access_is_relative_to_worktree() {
+        let conn = test_conn();
+        let entry = make_entry("files");
+        insert_process(&conn, &entry).unwrap();
+        let dir = entry.path.to_string_lossy().to_string();
+
+        for (tool, path) in [
+            ("Read", format!("{}/src/lib.rs", dir)),
+            ("Read", format!("{}/README.md", dir)),
+            ("Edit", format!("{}/src/lib.rs", dir)),
+            ("MultiEdit", format!("{}/src/lib.rs", dir)),
+            ("Write", format!("{}/src/new.rs", dir)),
+            ("Read", "/etc/hosts".to_string()),
+            // Same prefix, different directory
+            ("Read", format!("{}-other/x.rs", dir)),
+        ] {
+            let input = serde_json::json!({ "file_path": path }).to_string();
+            insert_tool_call(&conn, entry.id, "pre", tool, &input, None).unwrap();
+        }
+        insert_tool_call(&conn, entry.id, "pre", "Bash", r#"{"command":"ls"}"#, None).unwrap();
+
+        let files: Vec<_> = file_access(&conn, entry.id)
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.path, f.read, f.edited, f.written))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("src/lib.rs".to_string(), 1, 2, 0),
+                ("src/new.rs".to_string(), 0, 0, 1),
+                ("/etc/hosts".to_string(), 1, 0, 0),
+                (format!("{}-other/x.rs", dir), 1, 0, 0),
+                ("README.md".to_string(), 1, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shared_edits_across_live_sessions() {
+        let conn = test_conn();
+        let a = make_entry("overlap-a");
+        let b = make_entry("overlap-b");
+        let mut other_project = make_entry("overlap-c");
+        other_project.project = "other".to_string();
+        let archived = make_entry("overlap-d");
+        let exited = make_entry("overlap-e");
+        // Same prefix, different repo: compared only with each other
+        let mut elsewhere_a = make_entry("overlap-f");
+        elsewhere_a.git_common_dir = Some(PathBuf::from("/repos/tiny-pony/.git"));
+        let mut elsewhere_b = make_entry("overlap-g");
+        elsewhere_b.git_common_dir = elsewhere_a.git_common_dir.clone();
+        for entry in [&a, &b, &other_project, &archived, &exited, &elsewhere_a, &elsewhere_b] {
+            insert_process(&conn, entry).unwrap();
+        }
+        archive_process(&conn, archived.id).unwrap();
+        set_terminated(&conn, exited.id, ProcessStatus::Exited, 0).unwrap();
+
+        let edit = |entry: &Entry, tool: &str, file: &str| {
+            let input =
+                serde_json::json!({ "file_path": entry.path.join(file) }).to_string();
+            insert_tool_call(&conn, entry.id, "pre", tool, &input, None).unwrap()
+        };
+        edit(&a, "Edit", "src/lib.rs");
+        edit(&a, "Write", "src/lib.rs");
+        edit(&b, "MultiEdit", "src/lib.rs");
+        edit(&other_project, "Edit", "src/lib.rs");
+        edit(&archived, "Edit", "src/lib.rs");
+        edit(&exited, "Edit", "src/lib.rs");
+        edit(&a, "Edit", "src/main.rs");
+        edit(&b, "Read", "src/main.rs");
+        edit(&exited, "Edit", "src/main.rs");
+        edit(&elsewhere_a, "Edit", "src/main.rs");
+        edit(&elsewhere_b, "Edit", "src/main.rs");
+        // Denied by the policy, so never made
+        let denied = edit(&b, "Edit", "src/main.rs");
+        set_tool_call_decision(&conn, denied, "deny", Some("Protected file")).unwrap();
+
+        let shared = |since| -> Vec<_> {
+            shared_edits(&conn, since)
+                .unwrap()
+                .into_iter()
+                .map(|e| (e.repo, e.path, e.branch, e.edits))
+                .collect()
+        };
+        let elsewhere = "/repos/tiny-pony/.git".to_string();
+        assert_eq!(
+            shared(None),
+            vec![
+                (elsewhere.clone(), "src/main.rs".to_string(), "overlap-f".to_string(), 1),
+                (elsewhere.clone(), "src/main.rs".to_string(), "overlap-g".to_string(), 1),
+                ("tp".to_string(), "src/lib.rs".to_string(), "overlap-a".to_string(), 2),
+                ("tp".to_string(), "src/lib.rs".to_string(), "overlap-b".to_string(), 1),
+            ]
+        );
+        assert_eq!(shared(Some(Utc::now() - chrono::Duration::hours(1))).len(), 4);
+        assert!(shared(Some(Utc::now() + chrono::Duration::hours(1))).is_empty());
+    }
+
+    #[test]
+    fn test_tool_call_limit_keeps_most_recent() {
+        let conn = test_conn();
+        let entry = make_entry("limit");
+        insert_process(&conn, &entry).unwrap();
+        for tool in ["Read", "Edit", "Write", "Bash"] {
+            insert_tool_call(&conn, entry.id, "pre", tool, "{}", None).unwrap();
+        }
+
+        let calls = get_tool_calls(&conn, &for_process(entry.id), Some(2)).unwrap();
+        let names: Vec<_> = calls.iter().map(|c| c.tool_name.as_str()).collect();
+        assert_eq!(names, vec!["Write", "Bash"]);
+    }
+
     #[test]
     fn test_delete_process_cascades_tool_calls() {
         let conn = test_conn();
         let entry = make_entry("cascade");
         insert_process(&conn, &entry).unwrap();
-        insert_tool_call(&conn, entry.id, "pre", "Read", "{}").unwrap();
-        insert_tool_call(&conn, entry.id, "post", "Read", "{}").unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Read", "{}", None).unwrap();
+        insert_tool_call(&conn, entry.id, "post", "Read", "{}", None).unwrap();
 
         delete_process(&conn, entry.id).unwrap();
 
-        let calls = get_tool_calls_by_process(&conn, entry.id).unwrap();
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
         assert!(calls.is_empty());
     }
 
@@ -887,12 +2435,304 @@ mod tests {
         insert_process(&conn, &entry).unwrap();
 
         let complex = r#"{"command":"ls -la","timeout":5000,"nested":{"key":"value"}}"#;
-        insert_tool_call(&conn, entry.id, "pre", "Bash", complex).unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Bash", complex, None).unwrap();
 
-        let calls = get_tool_calls_by_process(&conn, entry.id).unwrap();
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
         assert_eq!(calls[0].tool_input, complex);
     }
 
+    #[test]
+    fn test_post_pairs_with_pre_by_tool_use_id() {
+        let conn = test_conn();
+        let entry = make_entry("pair");
+        insert_process(&conn, &entry).unwrap();
+
+        insert_tool_call(&conn, entry.id, "pre", "Bash", r#"{"command":"ls"}"#, Some("toolu_1")).unwrap();
+        insert_tool_call(&conn, entry.id, "pre", "Read", "{}", Some("toolu_2")).unwrap();
+        complete_tool_call(&conn, entry.id, Some("toolu_1"), "Bash", "{}", Some("a\nb"), false)
+            .unwrap();
+
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
+        assert_eq!(calls.len(), 2);
+
+        let bash = &calls[0];
+        assert_eq!(bash.tool_use_id.as_deref(), Some("toolu_1"));
+        assert_eq!(bash.hook_type, "post");
+        assert!(bash.ended_at.is_some());
+        assert!(bash.duration_ms.unwrap() >= 0);
+        assert_eq!(bash.is_error, Some(false));
+        assert_eq!(bash.tool_response.as_deref(), Some("a\nb"));
+        // Input recorded by the pre hook is kept
+        assert_eq!(bash.tool_input, r#"{"command":"ls"}"#);
+
+        let read = &calls[1];
+        assert_eq!(read.hook_type, "pre");
+        assert!(read.ended_at.is_none());
+        assert!(read.is_error.is_none());
+    }
+
+    #[test]
+    fn test_post_without_matching_pre_inserts_row() {
+        let conn = test_conn();
+        let entry = make_entry("orphan-post");
+        insert_process(&conn, &entry).unwrap();
+
+        complete_tool_call(&conn, entry.id, Some("toolu_9"), "Write", "{}", None, true).unwrap();
+
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].hook_type, "post");
+        assert_eq!(calls[0].is_error, Some(true));
+        assert!(calls[0].duration_ms.is_none());
+    }
+
+    #[test]
+    fn test_post_does_not_complete_other_process_call() {
+        let conn = test_conn();
+        let e1 = make_entry("p1");
+        let e2 = make_entry("p2");
+        insert_process(&conn, &e1).unwrap();
+        insert_process(&conn, &e2).unwrap();
+
+        insert_tool_call(&conn, e1.id, "pre", "Bash", "{}", Some("toolu_1")).unwrap();
+        complete_tool_call(&conn, e2.id, Some("toolu_1"), "Bash", "{}", None, false).unwrap();
+
+        let c1 = get_tool_calls(&conn, &for_process(e1.id), None).unwrap();
+        assert!(c1[0].ended_at.is_none());
+        assert_eq!(get_tool_calls(&conn, &for_process(e2.id), None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tool_response_is_truncated() {
+        let conn = test_conn();
+        let entry = make_entry("big-response");
+        insert_process(&conn, &entry).unwrap();
+
+        let big = "é".repeat(MAX_RESPONSE_CHARS + 50);
+        insert_tool_call(&conn, entry.id, "pre", "Read", "{}", Some("toolu_1")).unwrap();
+        complete_tool_call(&conn, entry.id, Some("toolu_1"), "Read", "{}", Some(&big), false)
+            .unwrap();
+
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
+        let stored = calls[0].tool_response.as_ref().unwrap();
+        assert_eq!(stored.chars().count(), MAX_RESPONSE_CHARS);
+    }
+
+    #[test]
+    fn test_migrate_schema_adds_tool_call_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tool_calls (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                process_id  TEXT NOT NULL,
+                tool_name   TEXT NOT NULL,
+                tool_input  TEXT,
+                hook_type   TEXT NOT NULL,
+                timestamp   TEXT NOT NULL,
+                sequence    INTEGER NOT NULL
+            );",
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+        // Running it twice must be harmless
+        init_schema(&conn).unwrap();
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('tool_calls')
+                 WHERE name IN ('tool_use_id', 'ended_at', 'duration_ms', 'is_error', 'tool_response',
+                                'decision', 'decision_reason')",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 7);
+    }
+
+    #[test]
+    fn test_migrate_schema_drops_unique_name() {
+        let conn = Connection::open_in_memory().unwrap();
+        configure_connection(&conn).unwrap();
+        // processes as created by earlier versions, name = branch
+        conn.execute_batch(
+            "CREATE TABLE processes (
+                id              TEXT PRIMARY KEY,
+                name            TEXT UNIQUE NOT NULL,
+                project         TEXT NOT NULL,
+                directory       TEXT NOT NULL,
+                branch          TEXT,
+                tmux_session    TEXT,
+                tmux_window     TEXT,
+                pid             INTEGER,
+                status          TEXT NOT NULL DEFAULT 'spawned',
+                blocked_on      TEXT,
+                exit_code       INTEGER,
+                command_json    TEXT NOT NULL,
+                exit_kill_json  TEXT,
+                prompt          TEXT,
+                created_at      TEXT NOT NULL,
+                updated_at      TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        let old = make_entry("fix-ci");
+        conn.execute(
+            "INSERT INTO processes
+                (id, name, project, directory, branch, tmux_session, tmux_window,
+                 command_json, created_at, updated_at)
+             VALUES (?1, 'fix-ci', 'tp', '/tmp/tp-fix-ci', 'fix-ci', 'dev', 'fix-ci',
+                     '{\"type\":\"raw\",\"cmd\":\"true\"}', ?2, ?2)",
+            params![old.id.to_string(), Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+        init_schema(&conn).unwrap();
+
+        let name: String = conn
+            .query_row("SELECT name FROM processes", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(name, "tp:fix-ci");
+
+        let mut other = make_entry("fix-ci");
+        other.project = "other".to_string();
+        insert_process(&conn, &other).unwrap();
+        insert_tool_call(&conn, old.id, "pre", "Read", "{}", None).unwrap();
+        assert_eq!(get_processes_by_branch(&conn, "fix-ci", false).unwrap().len(), 2);
+
+        let fk: i64 = conn
+            .query_row("PRAGMA foreign_keys", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(fk, 1);
+    }
+
+    #[test]
+    fn test_tool_calls_after_cursor() {
+        let conn = test_conn();
+        let e1 = make_entry("cursor-1");
+        let e2 = make_entry("cursor-2");
+        insert_process(&conn, &e1).unwrap();
+        insert_process(&conn, &e2).unwrap();
+        assert_eq!(last_tool_call_id(&conn).unwrap(), 0);
+
+        let first = insert_tool_call(&conn, e1.id, "pre", "Read", "{}", None).unwrap();
+        let cursor = last_tool_call_id(&conn).unwrap();
+        assert_eq!(cursor, first);
+
+        insert_tool_call(&conn, e2.id, "pre", "Bash", "{}", None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Write", "{}", None).unwrap();
+        insert_tool_call(&conn, e1.id, "pre", "Edit", "{}", None).unwrap();
+
+        let names = |calls: Vec<ToolCall>| calls.into_iter().map(|c| c.tool_name).collect::<Vec<_>>();
+        assert_eq!(
+            names(get_tool_calls_after(&conn, &for_process(e1.id), cursor).unwrap()),
+            vec!["Write", "Edit"]
+        );
+        assert_eq!(
+            names(get_tool_calls_after(&conn, &ToolCallFilter::default(), cursor).unwrap()),
+            vec!["Bash", "Write", "Edit"]
+        );
+        let last = last_tool_call_id(&conn).unwrap();
+        assert!(get_tool_calls_after(&conn, &ToolCallFilter::default(), last).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tool_stats() {
+        let conn = test_conn();
+        let e1 = make_entry("stats-1");
+        let e2 = make_entry("stats-2");
+        insert_process(&conn, &e1).unwrap();
+        insert_process(&conn, &e2).unwrap();
+
+        let calls = [
+            (e1.id, "Read", r#"{"file_path":"/src/a.rs"}"#),
+            (e1.id, "Read", r#"{"file_path":"/src/a.rs"}"#),
+            (e1.id, "Edit", r#"{"file_path":"/src/a.rs","old_string":"x"}"#),
+            (e1.id, "Write", r#"{"file_path":"/src/b.rs"}"#),
+            (e1.id, "Bash", r#"{"command":"cargo test"}"#),
+            (e1.id, "Bash", r#"{"command":"cargo test"}"#),
+            (e1.id, "Bash", r#"{"command":"ls"}"#),
+            (e1.id, "Bash", "not json"),
+            (e2.id, "Read", r#"{"file_path":"/other.rs"}"#),
+            (e1.id, "Write", r#"{"file_path":"/src/c.rs"}"#),
+        ];
+        let mut row_id = 0;
+        for (i, (id, tool, input)) in calls.iter().enumerate() {
+            let tool_use_id = format!("toolu_{}", i);
+            row_id = insert_tool_call(&conn, *id, "pre", tool, input, Some(&tool_use_id)).unwrap();
+        }
+        complete_tool_call(&conn, e1.id, Some("toolu_4"), "Bash", "", None, true).unwrap();
+        // The last Write was denied: counted as a call, but /src/c.rs was never touched
+        set_tool_call_decision(&conn, row_id, "deny", None).unwrap();
+
+        let stats = tool_stats(&conn, &for_process(e1.id)).unwrap();
+        assert_eq!((stats.calls, stats.failed, stats.sessions), (9, 1, 1));
+        assert_eq!(
+            stats.tools.iter().map(|t| (t.tool_name.as_str(), t.calls)).collect::<Vec<_>>(),
+            vec![("Bash", 4), ("Read", 2), ("Write", 2), ("Edit", 1)]
+        );
+        assert_eq!(stats.tools[0].failed, 1);
+        assert_eq!(
+            stats.files,
+            vec![
+                FileAccess { path: "/src/a.rs".to_string(), read: 2, edited: 1, written: 0 },
+                FileAccess { path: "/src/b.rs".to_string(), read: 0, edited: 0, written: 1 },
+            ]
+        );
+        assert_eq!(
+            stats.top_commands,
+            vec![
+                CommandCount { command: "cargo test".to_string(), calls: 2 },
+                CommandCount { command: "ls".to_string(), calls: 1 },
+            ]
+        );
+        assert!(stats.first_at.is_some() && stats.last_at >= stats.first_at);
+        assert_eq!(stats.calls_per_minute, Some(9.0));
+
+        let all = tool_stats(&conn, &ToolCallFilter::default()).unwrap();
+        assert_eq!((all.calls, all.sessions, all.files.len()), (10, 2, 3));
+
+        let empty = tool_stats(&conn, &for_process(Uuid::new_v4())).unwrap();
+        assert_eq!(empty.calls, 0);
+        assert!(empty.tools.is_empty() && empty.calls_per_minute.is_none());
+    }
+
+    // -- Event tests --------------------------------------------------------
+
+    #[test]
+    fn test_events_round_trip_and_cascade() {
+        let conn = test_conn();
+        let entry = make_entry("nudge");
+        insert_process(&conn, &entry).unwrap();
+
+        insert_event(&conn, entry.id, "input", Some("also add tests")).unwrap();
+        insert_event(&conn, entry.id, "input", Some("line 1\nline 2")).unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT kind, detail FROM events WHERE process_id = ?1 ORDER BY id")
+            .unwrap();
+        let events: Vec<(String, Option<String>)> = stmt
+            .query_map(params![entry.id.to_string()], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("input".to_string(), Some("also add tests".to_string())),
+                ("input".to_string(), Some("line 1\nline 2".to_string())),
+            ]
+        );
+        drop(stmt);
+
+        delete_process(&conn, entry.id).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM events", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
     // -- Migration tests ----------------------------------------------------
 
     #[test]
@@ -938,17 +2778,7 @@ mod tests {
         // First insert a process so the FK is satisfied
         let entry = Entry {
             id: Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap(),
-            project: "tp".to_string(),
-            branch: "feat-x".to_string(),
-            path: PathBuf::from("/tmp/tp-feat-x"),
-            tmux_session: "dev".to_string(),
-            tmux_window: "feat-x".to_string(),
-            command: Command::Raw {
-                cmd: "echo".to_string(),
-            },
-            exit_kill: None,
-            exit_code: None,
-            created_at: Utc::now(),
+            ..Entry::for_test("tp", "feat-x")
         };
         insert_process(&conn, &entry).unwrap();
 
@@ -974,7 +2804,7 @@ mod tests {
 
         migrate_tools_db(&conn, &tools_path).unwrap();
 
-        let calls = get_tool_calls_by_process(&conn, entry.id).unwrap();
+        let calls = get_tool_calls(&conn, &for_process(entry.id), None).unwrap();
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].tool_name, "Read");
         assert_eq!(calls[0].sequence, 1);
@@ -1006,7 +2836,7 @@ mod tests {
 
         migrate_tools_db(&conn, &tools_path).unwrap();
 
-        let all = get_all_tool_calls(&conn).unwrap();
+        let all = get_tool_calls(&conn, &ToolCallFilter::default(), None).unwrap();
         assert!(all.is_empty());
     }
 }
@@ -18,9 +18,12 @@ pub enum Error {
     #[error("Remote '{0}' not found")]
     RemoteNotFound(String),
 
-    #[error("Branch '{0}' already exists")]
+    #[error("Branch '{0}' already exists (use --existing to check it out)")]
     BranchExists(String),
 
+    #[error("Branch '{0}' not found locally or on remote '{1}'")]
+    BranchNotFound(String, String),
+
     #[error("Entry for branch '{0}' already exists in state (run `wortex cleanup` to remove stale entries)")]
     EntryExists(String),
 
@@ -36,9 +39,45 @@ pub enum Error {
     #[error("Entry not found: {0}")]
     EntryNotFound(String),
 
+    #[error("Entry not found: {0} (exists in {1}; use --project or project:branch)")]
+    EntryInOtherProject(String, String),
+
+    #[error("'{0}' is tracked in several repos ({1}); use --project or project:branch, or run from inside the repo")]
+    AmbiguousEntry(String, String),
+
     #[error("Tmux window '{0}' not found")]
     WindowNotFound(String),
 
+    #[error("The pane of '{0}' is dead (its command has exited; see `wortex restart`)")]
+    PaneDead(String),
+
+    #[error("No log recorded for '{0}'")]
+    NoLog(String),
+
+    #[error("'{0}' is still running (use --force to restart it anyway)")]
+    StillRunning(String),
+
+    #[error("Repository of '{0}' not found (its recorded repo and worktree no longer exist)")]
+    RepoNotFound(String),
+
+    #[error("Worktree '{0}' no longer exists")]
+    WorktreeMissing(PathBuf),
+
+    #[error("Uncommitted changes in '{0}':\n{1}")]
+    DirtyTree(PathBuf, String),
+
+    #[error("Merging '{0}' into '{1}' would conflict in:\n{2}")]
+    MergeConflict(String, String, String),
+
+    #[error("Cannot merge: {0}")]
+    MergeRefused(String),
+
+    #[error("Manifest error: {0}")]
+    Manifest(String),
+
+    #[error("{0} of {1} task(s) failed")]
+    BatchFailed(usize, usize),
+
     #[error("Git error: {0}")]
     Git(String),
 
@@ -54,8 +93,26 @@ pub enum Error {
     #[error("Database error: {0}")]
     Database(String),
 
-    #[error("Invalid hook type: {0} (expected 'pre' or 'post')")]
+    #[error("Invalid hook type: {0} (expected 'pre', 'post' or 'notification')")]
     InvalidHookType(String),
+
+    #[error("Config error: {0}")]
+    Config(String),
+
+    #[error("Policy error: {0}")]
+    Policy(String),
+
+    #[error("Notification failed: {0}")]
+    Notify(String),
+
+    #[error("Entry '{0}' was adopted and has no command to run")]
+    ExternalEntry(String),
+
+    #[error("'{0}' is not a linked worktree of this repository")]
+    NotAWorktree(PathBuf),
+
+    #[error("'{0}' is already tracked")]
+    AlreadyTracked(PathBuf),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,22 +1,30 @@
 use crate::error::{Error, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn is_git_repo() -> bool {
-    Command::new("git")
+/// Throwaway repositories for tests that run real git commands.
+#[cfg(test)]
+pub mod fixture;
+
+/// `git -C <repo>`: every operation runs against an explicit repository so
+/// commands behave the same from any working directory.
+fn git(repo: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo);
+    cmd
+}
+
+pub fn is_git_repo(repo: &Path) -> bool {
+    git(repo)
         .args(["rev-parse", "--git-dir"])
         .output()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
 
-pub fn is_worktree() -> Result<bool> {
-    let git_dir = Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .output()?;
-    let git_common_dir = Command::new("git")
-        .args(["rev-parse", "--git-common-dir"])
-        .output()?;
+pub fn is_worktree(repo: &Path) -> Result<bool> {
+    let git_dir = git(repo).args(["rev-parse", "--git-dir"]).output()?;
+    let git_common_dir = git(repo).args(["rev-parse", "--git-common-dir"]).output()?;
 
     let git_dir = String::from_utf8_lossy(&git_dir.stdout).trim().to_string();
     let git_common_dir = String::from_utf8_lossy(&git_common_dir.stdout)
@@ -26,17 +34,37 @@ pub fn is_worktree() -> Result<bool> {
     Ok(git_dir != git_common_dir)
 }
 
-pub fn remote_exists(remote: &str) -> Result<bool> {
-    let output = Command::new("git")
-        .args(["remote", "get-url", remote])
+/// Absolute path of the repository's shared git directory (`.git` of the main
+/// working tree), resolved from any directory inside the repo or one of its
+/// linked worktrees.
+pub fn git_common_dir(dir: &Path) -> Result<PathBuf> {
+    let output = git(dir)
+        .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
         .output()?;
+
+    if !output.status.success() {
+        return Err(Error::NotGitRepo);
+    }
+
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Root of the main working tree, resolved from any directory inside the repo
+/// or one of its linked worktrees.
+pub fn main_repo_root(dir: &Path) -> Result<PathBuf> {
+    git_common_dir(dir)?
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or(Error::NotGitRepo)
+}
+
+pub fn remote_exists(repo: &Path, remote: &str) -> Result<bool> {
+    let output = git(repo).args(["remote", "get-url", remote]).output()?;
     Ok(output.status.success())
 }
 
-pub fn get_remote_url(remote: &str) -> Result<String> {
-    let output = Command::new("git")
-        .args(["remote", "get-url", remote])
-        .output()?;
+pub fn get_remote_url(repo: &Path, remote: &str) -> Result<String> {
+    let output = git(repo).args(["remote", "get-url", remote]).output()?;
 
     if !output.status.success() {
         return Err(Error::RemoteNotFound(remote.to_string()));
@@ -45,17 +73,28 @@ pub fn get_remote_url(remote: &str) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-pub fn branch_exists(branch: &str) -> Result<bool> {
-    let output = Command::new("git")
+pub fn branch_exists(repo: &Path, branch: &str) -> Result<bool> {
+    let output = git(repo)
         .args(["show-ref", "--verify", "--quiet", &format!("refs/heads/{}", branch)])
         .output()?;
     Ok(output.status.success())
 }
 
-pub fn fetch(remote: &str) -> Result<()> {
-    let output = Command::new("git")
-        .args(["fetch", remote])
+/// Whether the remote-tracking ref `<remote>/<branch>` exists (call after `fetch`).
+pub fn remote_branch_exists(repo: &Path, remote: &str, branch: &str) -> Result<bool> {
+    let output = git(repo)
+        .args([
+            "show-ref",
+            "--verify",
+            "--quiet",
+            &format!("refs/remotes/{}/{}", remote, branch),
+        ])
         .output()?;
+    Ok(output.status.success())
+}
+
+pub fn fetch(repo: &Path, remote: &str) -> Result<()> {
+    let output = git(repo).args(["fetch", remote]).output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -65,8 +104,8 @@ pub fn fetch(remote: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn add_worktree(path: &Path, branch: &str, start_point: &str) -> Result<()> {
-    let output = Command::new("git")
+pub fn add_worktree(repo: &Path, path: &Path, branch: &str, start_point: &str) -> Result<()> {
+    let output = git(repo)
         .args([
             "worktree",
             "add",
@@ -85,8 +124,44 @@ pub fn add_worktree(path: &Path, branch: &str, start_point: &str) -> Result<()>
     Ok(())
 }
 
-pub fn remove_worktree(path: &Path) -> Result<()> {
-    let output = Command::new("git")
+/// Add a worktree checking out an existing local branch.
+pub fn add_worktree_existing(repo: &Path, path: &Path, branch: &str) -> Result<()> {
+    let output = git(repo)
+        .args(["worktree", "add", path.to_str().unwrap(), branch])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("worktree add failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Add a worktree with a new local branch tracking `remote_ref` (e.g. `origin/fix-ci`).
+pub fn add_worktree_tracking(repo: &Path, path: &Path, branch: &str, remote_ref: &str) -> Result<()> {
+    let output = git(repo)
+        .args([
+            "worktree",
+            "add",
+            "--track",
+            "-b",
+            branch,
+            path.to_str().unwrap(),
+            remote_ref,
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("worktree add failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+pub fn remove_worktree(repo: &Path, path: &Path) -> Result<()> {
+    let output = git(repo)
         .args(["worktree", "remove", "--force", path.to_str().unwrap()])
         .output()?;
 
@@ -98,10 +173,20 @@ pub fn remove_worktree(path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn delete_branch(branch: &str) -> Result<()> {
-    let output = Command::new("git")
-        .args(["branch", "-D", branch])
-        .output()?;
+/// Drop administrative files for worktrees whose directory is gone.
+pub fn prune_worktrees(repo: &Path) -> Result<()> {
+    let output = git(repo).args(["worktree", "prune"]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("worktree prune failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+pub fn delete_branch(repo: &Path, branch: &str) -> Result<()> {
+    let output = git(repo).args(["branch", "-D", branch]).output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -111,18 +196,414 @@ pub fn delete_branch(branch: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn status_short(path: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .args(["-C", path.to_str().unwrap(), "status", "-s"])
+/// One record from `git worktree list --porcelain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Worktree {
+    pub path: PathBuf,
+    pub head: Option<String>,
+    /// Short branch name; `None` when detached or bare
+    pub branch: Option<String>,
+    pub bare: bool,
+}
+
+/// All worktrees of the repository. The main worktree comes first.
+pub fn list_worktrees(repo: &Path) -> Result<Vec<Worktree>> {
+    let output = git(repo).args(["worktree", "list", "--porcelain"]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("worktree list failed: {}", stderr)));
+    }
+
+    Ok(parse_worktree_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_worktree_list(output: &str) -> Vec<Worktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<Worktree> = None;
+
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.extend(current.take());
+            current = Some(Worktree {
+                path: PathBuf::from(path),
+                head: None,
+                branch: None,
+                bare: false,
+            });
+        } else if let Some(wt) = current.as_mut() {
+            if let Some(head) = line.strip_prefix("HEAD ") {
+                wt.head = Some(head.to_string());
+            } else if let Some(branch) = line.strip_prefix("branch ") {
+                wt.branch = Some(branch.strip_prefix("refs/heads/").unwrap_or(branch).to_string());
+            } else if line == "bare" {
+                wt.bare = true;
+            }
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
+/// File counts and upstream tracking from `git status --porcelain=v2 --branch`.
+#[derive(Debug, Default, PartialEq)]
+pub struct StatusSummary {
+    pub staged: usize,
+    pub unstaged: usize,
+    pub untracked: usize,
+    /// Unmerged paths (conflicts of a merge or rebase in progress)
+    pub conflicted: usize,
+    /// e.g. `origin/fix-ci`; `None` without an upstream
+    pub upstream: Option<String>,
+    /// (ahead, behind) relative to the upstream
+    pub upstream_ahead_behind: Option<(usize, usize)>,
+}
+
+pub fn status_summary(path: &Path) -> Result<StatusSummary> {
+    let output = git(path)
+        .args(["status", "--porcelain=v2", "--branch"])
         .output()?;
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("status failed: {}", stderr)));
+    }
+
+    Ok(parse_status_v2(&String::from_utf8_lossy(&output.stdout)))
 }
 
-pub fn get_project_prefix(remote: &str) -> Result<String> {
-    let url = get_remote_url(remote)?;
-    let name = parse_repo_name(&url)?;
-    Ok(to_acronym(&name))
+fn parse_status_v2(output: &str) -> StatusSummary {
+    let mut summary = StatusSummary::default();
+    for line in output.lines() {
+        if let Some(upstream) = line.strip_prefix("# branch.upstream ") {
+            summary.upstream = Some(upstream.to_string());
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            // "+<ahead> -<behind>"
+            let mut parts = ab.split(' ');
+            let ahead = parts.next().and_then(|p| p.trim_start_matches('+').parse().ok());
+            let behind = parts.next().and_then(|p| p.trim_start_matches('-').parse().ok());
+            if let (Some(ahead), Some(behind)) = (ahead, behind) {
+                summary.upstream_ahead_behind = Some((ahead, behind));
+            }
+        } else if line.starts_with("1 ") || line.starts_with("2 ") {
+            // "<kind> <XY> ...": X is the index, Y the working tree; '.' = unchanged
+            let mut xy = line[2..].chars();
+            if xy.next().is_some_and(|x| x != '.') {
+                summary.staged += 1;
+            }
+            if xy.next().is_some_and(|y| y != '.') {
+                summary.unstaged += 1;
+            }
+        } else if line.starts_with("u ") {
+            summary.conflicted += 1;
+        } else if line.starts_with("? ") {
+            summary.untracked += 1;
+        }
+    }
+    summary
+}
+
+/// (ahead, behind): commits on `head` not on `base`, and on `base` not on `head`.
+pub fn ahead_behind(path: &Path, base: &str, head: &str) -> Result<(usize, usize)> {
+    let output = git(path)
+        .args(["rev-list", "--left-right", "--count", &format!("{}...{}", base, head)])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("rev-list failed: {}", stderr)));
+    }
+
+    // "<only in base>\t<only in head>"
+    let counts: Vec<usize> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .filter_map(|c| c.parse().ok())
+        .collect();
+    match counts[..] {
+        [behind, ahead] => Ok((ahead, behind)),
+        _ => Err(Error::Git("rev-list returned unexpected counts".to_string())),
+    }
+}
+
+/// Subject and commit time (Unix seconds) of HEAD; `None` on an unborn branch.
+pub fn last_commit(path: &Path) -> Result<Option<(String, i64)>> {
+    let output = git(path).args(["log", "-1", "--format=%ct %s"]).output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let line = String::from_utf8_lossy(&output.stdout).trim_end().to_string();
+    Ok(line.split_once(' ').and_then(|(time, subject)| {
+        time.parse().ok().map(|time| (subject.to_string(), time))
+    }))
+}
+
+/// "merging" or "rebasing" when the worktree at `path` is in the middle of one.
+pub fn operation_in_progress(path: &Path) -> Result<Option<&'static str>> {
+    let output = git(path)
+        .args(["rev-parse", "--path-format=absolute", "--git-dir"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::NotGitRepo);
+    }
+
+    let git_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        Ok(Some("rebasing"))
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Ok(Some("merging"))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Commit SHA that `rev` resolves to.
+pub fn rev_parse(repo: &Path, rev: &str) -> Result<String> {
+    let output = git(repo)
+        .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::Git(format!("cannot resolve '{}'", rev)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Best common ancestor of two commits.
+pub fn merge_base(repo: &Path, a: &str, b: &str) -> Result<String> {
+    let output = git(repo).args(["merge-base", a, b]).output()?;
+
+    if !output.status.success() {
+        return Err(Error::Git(format!("no merge base between '{}' and '{}'", a, b)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `git diff <rev> <args>` for the working tree at `path`, including files
+/// that are not tracked yet (unless ignored). Untracked files are marked
+/// intent-to-add in a copy of the index, so the real index is left alone.
+pub fn diff_working_tree(path: &Path, rev: &str, args: &[&str]) -> Result<String> {
+    let output = git(path)
+        .args(["rev-parse", "--path-format=absolute", "--git-path", "index"])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::NotGitRepo);
+    }
+    let index = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let temp_index = index.with_extension("wortex-diff");
+    if index.exists() {
+        std::fs::copy(&index, &temp_index)?;
+    }
+
+    let result = (|| {
+        let output = git(path)
+            .env("GIT_INDEX_FILE", &temp_index)
+            .args(["add", "--intent-to-add", "--all"])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Git(format!("add --intent-to-add failed: {}", stderr)));
+        }
+
+        let output = git(path)
+            .env("GIT_INDEX_FILE", &temp_index)
+            .arg("diff")
+            .arg(rev)
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Git(format!("diff failed: {}", stderr)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    })();
+
+    let _ = std::fs::remove_file(&temp_index);
+    result
+}
+
+/// Push `branch` to the same name on `remote` and make it the upstream.
+pub fn push(repo: &Path, remote: &str, branch: &str, force_with_lease: bool) -> Result<()> {
+    let mut cmd = git(repo);
+    cmd.args(["push", "--set-upstream"]);
+    if force_with_lease {
+        cmd.arg("--force-with-lease");
+    }
+    let output = cmd.args([remote, branch]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("push failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Branch checked out in the working tree at `path`; `None` when detached.
+pub fn current_branch(path: &Path) -> Result<Option<String>> {
+    let output = git(path).args(["symbolic-ref", "--quiet", "--short", "HEAD"]).output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Uncommitted changes to tracked files (staged or not), one `git status
+/// --porcelain` line per file. Untracked files are ignored.
+pub fn uncommitted_changes(path: &Path) -> Result<Vec<String>> {
+    let output = git(path)
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("status failed: {}", stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Number of commits reachable from `to` but not from `from` (`from..to`).
+pub fn count_commits(repo: &Path, from: &str, to: &str) -> Result<usize> {
+    let output = git(repo)
+        .args(["rev-list", "--count", &format!("{}..{}", from, to)])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("rev-list failed: {}", stderr)));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .map_err(|_| Error::Git("rev-list returned a non-numeric count".to_string()))
+}
+
+/// Files that would conflict if `branch` were merged into `base`. Computed
+/// with `git merge-tree`, which touches neither the index nor a working tree.
+pub fn merge_conflicts(repo: &Path, base: &str, branch: &str) -> Result<Vec<String>> {
+    let output = git(repo)
+        .args(["merge-tree", "--write-tree", "--name-only", "--no-messages", base, branch])
+        .output()?;
+
+    // Exit status 1 means the merge has conflicts; anything else is an error
+    match output.status.code() {
+        Some(0) => Ok(Vec::new()),
+        Some(1) => Ok(parse_merge_tree_conflicts(&String::from_utf8_lossy(&output.stdout))),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::Git(format!("merge-tree failed: {}", stderr)))
+        }
+    }
+}
+
+/// The conflicted file list of `git merge-tree --name-only` output: the lines
+/// after the tree OID, up to the first blank line.
+fn parse_merge_tree_conflicts(output: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for line in output.lines().skip(1).take_while(|l| !l.is_empty()) {
+        if !files.iter().any(|f| f == line) {
+            files.push(line.to_string());
+        }
+    }
+    files
+}
+
+/// Merge `branch` into the branch checked out at `repo`. With `ff_only`,
+/// refuse anything but a fast-forward.
+pub fn merge(repo: &Path, branch: &str, ff_only: bool) -> Result<()> {
+    let mode = if ff_only { "--ff-only" } else { "--no-edit" };
+    let output = git(repo).args(["merge", mode, branch]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Leave the tree as it was if the merge stopped half-way
+        let _ = git(repo).args(["merge", "--abort"]).output();
+        return Err(Error::Git(format!("merge failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Squash `branch` into a single commit on the branch checked out at `repo`,
+/// using git's generated squash message.
+pub fn merge_squash(repo: &Path, branch: &str) -> Result<()> {
+    let output = git(repo).args(["merge", "--squash", branch]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let _ = git(repo).args(["reset", "--merge"]).output();
+        return Err(Error::Git(format!("merge --squash failed: {}", stderr)));
+    }
+
+    let output = git(repo).args(["commit", "--no-edit"]).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let _ = git(repo).args(["reset", "--merge"]).output();
+        return Err(Error::Git(format!("commit failed: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Rebase the branch checked out at `path` onto `onto`. On conflict the
+/// rebase is aborted and the conflicted files are returned.
+pub fn rebase(path: &Path, onto: &str) -> Result<Vec<String>> {
+    let output = git(path).args(["rebase", onto]).output()?;
+
+    if output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let conflicts = git(path)
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .output()?;
+    let conflicts: Vec<String> = String::from_utf8_lossy(&conflicts.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let _ = git(path).args(["rebase", "--abort"]).output();
+
+    if conflicts.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("rebase failed: {}", stderr)));
+    }
+    Ok(conflicts)
+}
+
+pub fn get_project_prefix(repo: &Path, remote: &str) -> Result<String> {
+    Ok(to_acronym(&get_repo_name(repo, remote)?))
+}
+
+/// Project prefix of the repo containing `dir`, or `None` outside a repo.
+/// Falls back to the main repo's directory name when the remote is missing.
+pub fn current_project(dir: &Path, remote: &str) -> Option<String> {
+    if !is_git_repo(dir) {
+        return None;
+    }
+    get_project_prefix(dir, remote).ok().or_else(|| {
+        let root = main_repo_root(dir).ok()?;
+        let name = root.file_name()?.to_string_lossy().to_string();
+        Some(to_acronym(&name))
+    })
+}
+
+/// Repository name from the remote URL, e.g. `my-project` for
+/// `git@github.com:user/my-project.git`.
+pub fn get_repo_name(repo: &Path, remote: &str) -> Result<String> {
+    let url = get_remote_url(repo, remote)?;
+    parse_repo_name(&url)
 }
 
 fn parse_repo_name(url: &str) -> Result<String> {
@@ -139,7 +620,7 @@ fn parse_repo_name(url: &str) -> Result<String> {
     Ok(name.to_string())
 }
 
-fn to_acronym(name: &str) -> String {
+pub fn to_acronym(name: &str) -> String {
     // Split on '-' or '_'
     let parts: Vec<&str> = name.split(['-', '_']).collect();
 
@@ -185,6 +666,43 @@ mod tests {
         assert_eq!(to_acronym("foo-bar_baz"), "fbb");
     }
 
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "worktree /repos/my-project
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /repos/mp-feature-login
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/feature/login
+
+worktree /repos/mp-detached
+HEAD 3333333333333333333333333333333333333333
+detached
+
+";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].path, PathBuf::from("/repos/my-project"));
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].branch.as_deref(), Some("feature/login"));
+        assert_eq!(
+            worktrees[1].head.as_deref(),
+            Some("2222222222222222222222222222222222222222")
+        );
+        assert_eq!(worktrees[2].branch, None);
+        assert!(!worktrees[2].bare);
+    }
+
+    #[test]
+    fn test_parse_worktree_list_bare() {
+        let output = "worktree /repos/project.git\nbare\n";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 1);
+        assert!(worktrees[0].bare);
+        assert_eq!(worktrees[0].branch, None);
+    }
+
     #[test]
     fn test_parse_repo_name_ssh() {
         assert_eq!(
@@ -208,4 +726,53 @@ mod tests {
             "myproject"
         );
     }
+
+    #[test]
+    fn test_parse_merge_tree_conflicts() {
+        let output = "d42f6e61ad4e0d57aafb840bff3a24b687535dec
+src/main.rs
+src/main.rs
+README.md
+
+Auto-merging README.md
+";
+        assert_eq!(
+            parse_merge_tree_conflicts(output),
+            vec!["src/main.rs".to_string(), "README.md".to_string()]
+        );
+        assert!(parse_merge_tree_conflicts("d42f6e61ad4e0d57aafb840bff3a24b687535dec\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_status_v2() {
+        let output = "# branch.oid 1111111111111111111111111111111111111111
+# branch.head fix-ci
+# branch.upstream origin/fix-ci
+# branch.ab +2 -1
+1 M. N... 100644 100644 100644 aaaa bbbb src/a.rs
+1 .M N... 100644 100644 100644 aaaa bbbb src/b.rs
+1 MM N... 100644 100644 100644 aaaa bbbb src/c.rs
+2 R. N... 100644 100644 100644 aaaa bbbb R100 src/d.rs	src/old.rs
+u UU N... 100644 100644 100644 100644 aaaa bbbb cccc src/e.rs
+? notes.txt
+? tmp/
+";
+        assert_eq!(
+            parse_status_v2(output),
+            StatusSummary {
+                staged: 3,
+                unstaged: 2,
+                untracked: 2,
+                conflicted: 1,
+                upstream: Some("origin/fix-ci".to_string()),
+                upstream_ahead_behind: Some((2, 1)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_status_v2_without_upstream() {
+        let output = "# branch.oid 1111111111111111111111111111111111111111\n# branch.head fix-ci\n";
+        assert_eq!(parse_status_v2(output), StatusSummary::default());
+    }
 }
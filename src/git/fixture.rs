@@ -0,0 +1,62 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Run git in `dir`, panicking with its stderr on failure; returns stdout.
+pub fn run_git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?}: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Run git in `dir` where failure is expected or allowed.
+pub fn try_git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .success()
+}
+
+/// Write `file` and commit it on the current branch.
+pub fn commit(dir: &Path, file: &str, content: &str) {
+    fs::write(dir.join(file), content).unwrap();
+    run_git(dir, &["add", file]);
+    run_git(dir, &["commit", "-q", "-m", &format!("edit {}", file)]);
+}
+
+/// Create a repo at `dir` on `branch` with one commit of `README.md`.
+pub fn init_repo(dir: &Path, branch: &str) {
+    fs::create_dir_all(dir).unwrap();
+    run_git(dir, &["init", "-q", "-b", branch]);
+    run_git(dir, &["config", "user.name", "Test"]);
+    run_git(dir, &["config", "user.email", "test@example.com"]);
+    commit(dir, "README.md", "hello\n");
+}
+
+/// A repo on `main` with a `feature` worktree next to it.
+pub fn repo_with_worktree() -> (TempDir, PathBuf, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path().join("repo");
+    let worktree = temp_dir.path().join("repo-feature");
+    init_repo(&repo, "main");
+    run_git(
+        &repo,
+        &["worktree", "add", "-q", worktree.to_str().unwrap(), "-b", "feature"],
+    );
+    (temp_dir, repo, worktree)
+}
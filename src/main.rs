@@ -1,14 +1,25 @@
 mod cli;
 mod commands;
+mod config;
 mod db;
 mod error;
 mod git;
+mod notify;
+mod policy;
 mod state;
 mod tmux;
 
 use clap::Parser;
-use cli::{Cli, Commands, ExitKillArg};
+use cli::{Cli, Commands, ConfigCommands, ExitKillArg, ToolsCommands};
+use commands::batch::BatchArgs;
+use commands::config::ConfigAction;
+use commands::diff::{DiffArgs, DiffFormat};
+use commands::export::ExportArgs;
+use commands::files::FilesArgs;
+use commands::merge::{MergeArgs, MergeStrategy};
 use commands::new::NewArgs;
+use commands::push::PushArgs;
+use commands::status::StatusArgs;
 
 fn main() {
     let cli = Cli::parse();
@@ -23,19 +34,42 @@ fn main() {
             exit_kill,
             remote,
             base,
+            existing,
         } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::new::execute(NewArgs {
-                branch,
-                prompt,
-                cmd,
-                agent,
-                exit_kill: ExitKillArg::parse(exit_kill),
-                remote,
-                base,
+            load_config().and_then(|config| {
+                commands::new::execute(NewArgs {
+                    branch,
+                    prompt,
+                    cmd,
+                    agent: agent.or(config.new.agent.clone()),
+                    exit_kill: exit_kill_arg(exit_kill, &config)?,
+                    remote: remote.unwrap_or_else(|| config.remote().to_string()),
+                    base: base.unwrap_or_else(|| config.base().to_string()),
+                    worktree_dir: config.worktree_dir(),
+                    path_template: config.path_template().to_string(),
+                    existing,
+                })
+            })
+        }
+        Commands::Batch { manifest, remote } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::batch::execute(BatchArgs {
+                    manifest,
+                    remote: remote.unwrap_or_else(|| config.remote().to_string()),
+                    agent: config.new.agent.clone(),
+                    base: config.base().to_string(),
+                    exit_kill: config.new.exit_kill.clone(),
+                    worktree_dir: config.worktree_dir(),
+                    path_template: config.path_template().to_string(),
+                })
             })
         }
         Commands::Run { id } => {
@@ -45,43 +79,212 @@ fn main() {
             }
             commands::run::execute(&id)
         }
-        Commands::List { json } => {
+        Commands::List { json, no_json } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::list::execute(json)
+            load_config().and_then(|config| {
+                commands::list::execute(cli::flag(json, no_json).or(config.list.json).unwrap_or(false))
+            })
         }
-        Commands::Switch { branch } => {
+        Commands::Switch { branch, project } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::switch::execute(&branch)
+            load_config().and_then(|config| {
+                commands::switch::execute(&branch, &project_scope(project, &config))
+            })
         }
         Commands::Kill {
             branch,
+            project,
+            keep_worktree,
+            no_keep_worktree,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::kill::execute(
+                    &branch,
+                    &project_scope(project, &config),
+                    cli::flag(keep_worktree, no_keep_worktree)
+                        .or(config.kill.keep_worktree)
+                        .unwrap_or(false),
+                )
+            })
+        }
+        Commands::Merge {
+            branch,
+            project,
+            squash,
+            rebase,
+            ff_only,
+            into,
+            kill,
             keep_worktree,
+            no_keep_worktree,
         } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::kill::execute(&branch, keep_worktree)
+            load_config().and_then(|config| {
+                commands::merge::execute(MergeArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    strategy: MergeStrategy::from_flags(squash, rebase, ff_only),
+                    into,
+                    base: config.base().to_string(),
+                    kill,
+                    keep_worktree: cli::flag(keep_worktree, no_keep_worktree)
+                        .or(config.kill.keep_worktree)
+                        .unwrap_or(false),
+                })
+            })
         }
-        Commands::Cleanup { dry_run } => {
+        Commands::Push {
+            branch,
+            project,
+            force_with_lease,
+        } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::cleanup::execute(dry_run)
+            load_config().and_then(|config| {
+                commands::push::execute(PushArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    remote: config.remote().to_string(),
+                    force_with_lease,
+                })
+            })
+        }
+        Commands::Diff {
+            branch,
+            project,
+            stat,
+            name_only,
+            patch: _,
+            json,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::diff::execute(DiffArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    format: DiffFormat::from_flags(stat, name_only),
+                    json,
+                    remote: config.remote().to_string(),
+                    base: config.base().to_string(),
+                })
+            })
+        }
+        Commands::Send {
+            branch,
+            text,
+            project,
+            enter,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::send::execute(commands::send::SendArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    text,
+                    enter,
+                })
+            })
+        }
+        Commands::Wait {
+            branches,
+            project,
+            any,
+            all: _,
+            timeout,
+            json,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::wait::execute(commands::wait::WaitArgs {
+                    branches,
+                    scope: project_scope(project, &config),
+                    any,
+                    timeout,
+                    json,
+                })
+            })
+        }
+        Commands::Restart {
+            branch,
+            project,
+            force,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::restart::execute(commands::restart::RestartArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    force,
+                })
+            })
         }
-        Commands::Status => {
+        Commands::Cleanup { dry_run, purge } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::status::execute()
+            commands::cleanup::execute(dry_run, purge)
+        }
+        Commands::Status { json } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::status::execute(StatusArgs {
+                    json,
+                    remote: config.remote().to_string(),
+                    base: config.base().to_string(),
+                })
+            })
+        }
+        Commands::Files {
+            branch,
+            project,
+            overlap,
+            since,
+            json,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::files::execute(FilesArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    overlap,
+                    since,
+                    json,
+                })
+            })
         }
         Commands::LogTool {
             session_id,
@@ -94,20 +297,109 @@ fn main() {
             commands::log_tool::execute(&session_id, &hook_type)
         }
         Commands::Tools {
+            action: Some(ToolsCommands::Export {
+                branch,
+                project,
+                format,
+            }),
+            ..
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::export::execute(ExportArgs {
+                    branch,
+                    scope: project_scope(project.clone(), &config),
+                    project,
+                    format,
+                })
+            })
+        }
+        Commands::Tools {
+            action: None,
             branch,
+            project,
             json,
             hook_type,
             limit,
+            tools,
+            since,
+            until,
+            status,
+            exit_code,
+            grep,
+            regex,
+            stats,
+            follow,
         } => {
             if let Err(e) = state::ensure_initialized() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
-            commands::tools::execute(commands::tools::ToolsArgs {
-                branch,
-                json,
-                hook_type,
-                limit,
+            load_config().and_then(|config| {
+                commands::tools::execute(commands::tools::ToolsArgs {
+                    branch,
+                    scope: project_scope(project.clone(), &config),
+                    json,
+                    hook_type,
+                    limit,
+                    tools,
+                    since,
+                    until,
+                    project,
+                    status,
+                    exit_code,
+                    grep,
+                    regex,
+                    stats,
+                    follow,
+                })
+            })
+        }
+        Commands::Logs {
+            branch,
+            project,
+            follow,
+            raw,
+        } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::logs::execute(commands::logs::LogsArgs {
+                    branch,
+                    scope: project_scope(project, &config),
+                    follow,
+                    raw,
+                })
+            })
+        }
+        Commands::Adopt { path, all, remote } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            load_config().and_then(|config| {
+                commands::adopt::execute(commands::adopt::AdoptArgs {
+                    path,
+                    all,
+                    remote: remote.unwrap_or_else(|| config.remote().to_string()),
+                })
+            })
+        }
+        Commands::Config { action } => {
+            if let Err(e) = state::ensure_initialized() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            commands::config::execute(match action {
+                ConfigCommands::Get { key } => ConfigAction::Get { key },
+                ConfigCommands::Set { key, value, repo } => ConfigAction::Set { key, value, repo },
+                ConfigCommands::Unset { key, repo } => ConfigAction::Unset { key, repo },
+                ConfigCommands::List => ConfigAction::List,
             })
         }
     };
@@ -117,3 +409,40 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+/// Layered config for the repo containing the current directory.
+fn load_config() -> error::Result<config::Config> {
+    config::load(&std::env::current_dir()?)
+}
+
+/// `--exit-kill` as given, else `new.exit_kill`. A config value that is not
+/// "any" or a list of codes is an error rather than a silent exit 0.
+fn exit_kill_arg(
+    value: Option<Option<String>>,
+    config: &config::Config,
+) -> error::Result<Option<ExitKillArg>> {
+    match (value, &config.new.exit_kill) {
+        (Some(value), _) => Ok(ExitKillArg::parse(Some(value))),
+        (None, Some(text)) => ExitKillArg::parse_text(text)
+            .map(Some)
+            .map_err(|e| error::Error::Config(format!("new.exit_kill: {}", e))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Where branch arguments are looked up: `--project`, else the repo containing
+/// the current directory, else every repo.
+fn project_scope(project: Option<String>, config: &config::Config) -> state::ProjectScope {
+    if let Some(project) = project {
+        return state::ProjectScope::Explicit(project);
+    }
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| {
+            Some(state::ProjectScope::Current {
+                project: git::current_project(&dir, config.remote())?,
+                git_common_dir: git::git_common_dir(&dir).ok()?,
+            })
+        })
+        .unwrap_or(state::ProjectScope::Any)
+}
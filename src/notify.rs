@@ -0,0 +1,338 @@
+use crate::config::{self, Config};
+use crate::error::{Error, Result};
+use crate::state::{Entry, ProcessStatus};
+use crate::tmux;
+use chrono::Utc;
+use serde_json::json;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `notify.command` may run before it is killed. Callers such as
+/// `__run` wait for it, so a hanging command must not block them.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Lifecycle events a notification can be sent for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Command exited with code 0
+    ExitedOk,
+    /// Command exited non-zero or was killed by a signal
+    ExitedFail,
+    /// Agent is blocked on the user (see the Notification hook)
+    Waiting,
+    /// A `wortex wait --timeout` gave up on the session
+    Timeout,
+}
+
+impl Event {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Event::ExitedOk => "exited_ok",
+            Event::ExitedFail => "exited_fail",
+            Event::Waiting => "waiting",
+            Event::Timeout => "timeout",
+        }
+    }
+
+    /// The event for a command that terminated with `status` and `code`.
+    pub fn for_exit(status: ProcessStatus, code: i32) -> Self {
+        if status == ProcessStatus::Exited && code == 0 {
+            Event::ExitedOk
+        } else {
+            Event::ExitedFail
+        }
+    }
+}
+
+/// Where a notification goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// `tmux display-message` in the session
+    Tmux,
+    /// Bell in the entry's window, so it is flagged in the status line
+    Bell,
+    /// `notify.command`, with the event as JSON on stdin
+    Command,
+}
+
+impl Channel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Tmux => "tmux",
+            Channel::Bell => "bell",
+            Channel::Command => "command",
+        }
+    }
+}
+
+/// Channels configured for `event` (`notify.<event>`, else the default).
+pub fn channels(config: &Config, event: Event) -> Result<Vec<Channel>> {
+    let (value, default) = match event {
+        Event::ExitedOk => (&config.notify.exited_ok, config::DEFAULT_NOTIFY_EXITED_OK),
+        Event::ExitedFail => (&config.notify.exited_fail, config::DEFAULT_NOTIFY_ALERT),
+        Event::Waiting => (&config.notify.waiting, config::DEFAULT_NOTIFY_ALERT),
+        Event::Timeout => (&config.notify.timeout, config::DEFAULT_NOTIFY_ALERT),
+    };
+    parse_channels(value.as_deref().unwrap_or(default))
+}
+
+fn parse_channels(value: &str) -> Result<Vec<Channel>> {
+    let mut channels = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let channel = match name {
+            "" | "none" => continue,
+            "tmux" => Channel::Tmux,
+            "bell" => Channel::Bell,
+            "command" => Channel::Command,
+            other => {
+                return Err(Error::Config(format!(
+                    "unknown notification channel '{}' (expected tmux, bell or command)",
+                    other
+                )))
+            }
+        };
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+    Ok(channels)
+}
+
+/// Send `event` for `entry` on its configured channels. Notifications are
+/// best effort: failures are reported on stderr and never fail the caller.
+pub fn emit(event: Event, entry: &Entry, message: Option<&str>) {
+    if let Err(e) = try_emit(event, entry, message) {
+        eprintln!("wortex: {} notification failed: {}", event.as_str(), e);
+    }
+}
+
+fn try_emit(event: Event, entry: &Entry, message: Option<&str>) -> Result<()> {
+    let config = config::load(&entry.path)?;
+    for channel in channels(&config, event)? {
+        let result = match channel {
+            Channel::Tmux => tmux::display_message(
+                &entry.tmux_session,
+                &entry.tmux_window,
+                &summary(event, entry, message),
+            ),
+            Channel::Bell => tmux::ring_bell(&entry.tmux_session, &entry.tmux_window),
+            // Listing `command` without configuring one is not an error
+            Channel::Command => match &config.notify.command {
+                Some(command) => {
+                    run_command(command, &payload(event, entry, message), COMMAND_TIMEOUT)
+                }
+                None => Ok(()),
+            },
+        };
+        if let Err(e) = result {
+            eprintln!(
+                "wortex: {} notification via {} failed: {}",
+                event.as_str(),
+                channel.as_str(),
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// One-line text for the tmux status line.
+fn summary(event: Event, entry: &Entry, message: Option<&str>) -> String {
+    let name = format!("{}:{}", entry.project, entry.branch);
+    let code = entry
+        .exit_code
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    match event {
+        Event::ExitedOk => format!("wortex: {} finished", name),
+        Event::ExitedFail => format!("wortex: {} failed (exit {})", name, code),
+        Event::Waiting => match message {
+            Some(message) => format!("wortex: {} is waiting: {}", name, message),
+            None => format!("wortex: {} is waiting", name),
+        },
+        Event::Timeout => format!("wortex: {} timed out", name),
+    }
+}
+
+/// JSON document passed to `notify.command` on stdin.
+fn payload(event: Event, entry: &Entry, message: Option<&str>) -> serde_json::Value {
+    json!({
+        "event": event.as_str(),
+        "id": entry.id,
+        "project": entry.project,
+        "branch": entry.branch,
+        "path": entry.path,
+        "tmux_session": entry.tmux_session,
+        "tmux_window": entry.tmux_window,
+        "status": entry.status.as_str(),
+        "exit_code": entry.exit_code,
+        "message": message,
+        "timestamp": Utc::now().to_rfc3339(),
+    })
+}
+
+/// Run `command` with `payload` on stdin; killed once `timeout` has passed.
+fn run_command(command: &str, payload: &serde_json::Value, timeout: Duration) -> Result<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .env("WORTEX_EVENT", payload["event"].as_str().unwrap_or_default())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(format!("{}\n", payload).as_bytes())?;
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::Notify(format!(
+                "'{}' did not finish within {:?} and was killed",
+                command, timeout
+            )));
+        }
+        thread::sleep(COMMAND_POLL_INTERVAL);
+    };
+    if !status.success() {
+        return Err(Error::Notify(format!("'{}' exited with {}", command, status)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotifyConfig;
+    use crate::state::Command as EntryCommand;
+
+    fn make_entry() -> Entry {
+        Entry {
+            command: EntryCommand::Raw {
+                cmd: "false".to_string(),
+            },
+            exit_code: Some(1),
+            status: ProcessStatus::Exited,
+            pid: Some(42),
+            run_count: 1,
+            ..Entry::for_test("mp", "fix-ci")
+        }
+    }
+
+    #[test]
+    fn test_event_for_exit() {
+        assert_eq!(Event::for_exit(ProcessStatus::Exited, 0), Event::ExitedOk);
+        assert_eq!(Event::for_exit(ProcessStatus::Exited, 2), Event::ExitedFail);
+        assert_eq!(Event::for_exit(ProcessStatus::Signaled, 143), Event::ExitedFail);
+    }
+
+    #[test]
+    fn test_default_channels() {
+        let config = Config::default();
+        assert_eq!(channels(&config, Event::ExitedOk).unwrap(), vec![Channel::Tmux]);
+        assert_eq!(
+            channels(&config, Event::Waiting).unwrap(),
+            vec![Channel::Tmux, Channel::Bell, Channel::Command]
+        );
+        assert_eq!(
+            channels(&config, Event::Timeout).unwrap(),
+            vec![Channel::Tmux, Channel::Bell, Channel::Command]
+        );
+    }
+
+    #[test]
+    fn test_timeout_event() {
+        let config = Config {
+            notify: NotifyConfig {
+                timeout: Some("bell".to_string()),
+                ..NotifyConfig::default()
+            },
+            ..Config::default()
+        };
+        assert_eq!(channels(&config, Event::Timeout).unwrap(), vec![Channel::Bell]);
+        assert_eq!(Event::Timeout.as_str(), "timeout");
+        assert_eq!(
+            summary(Event::Timeout, &make_entry(), None),
+            "wortex: mp:fix-ci timed out"
+        );
+    }
+
+    #[test]
+    fn test_configured_channels() {
+        let config = Config {
+            notify: NotifyConfig {
+                exited_ok: Some("none".to_string()),
+                exited_fail: Some(" command , bell,bell".to_string()),
+                ..NotifyConfig::default()
+            },
+            ..Config::default()
+        };
+        assert!(channels(&config, Event::ExitedOk).unwrap().is_empty());
+        assert_eq!(
+            channels(&config, Event::ExitedFail).unwrap(),
+            vec![Channel::Command, Channel::Bell]
+        );
+    }
+
+    #[test]
+    fn test_unknown_channel_is_config_error() {
+        assert!(matches!(parse_channels("tmux,email"), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_payload() {
+        let entry = make_entry();
+        let payload = payload(Event::ExitedFail, &entry, None);
+        assert_eq!(payload["event"], "exited_fail");
+        assert_eq!(payload["branch"], "fix-ci");
+        assert_eq!(payload["exit_code"], 1);
+        assert_eq!(payload["status"], "exited");
+        assert!(payload["message"].is_null());
+    }
+
+    #[test]
+    fn test_summary() {
+        let entry = make_entry();
+        assert_eq!(
+            summary(Event::ExitedFail, &entry, None),
+            "wortex: mp:fix-ci failed (exit 1)"
+        );
+        assert_eq!(
+            summary(Event::Waiting, &entry, Some("Claude needs your permission to use Bash")),
+            "wortex: mp:fix-ci is waiting: Claude needs your permission to use Bash"
+        );
+    }
+
+    #[test]
+    fn test_run_command_gets_payload_on_stdin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("event.json");
+        let payload = payload(Event::Waiting, &make_entry(), Some("waiting for input"));
+
+        run_command(&format!("cat > '{}'", out.display()), &payload, COMMAND_TIMEOUT).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(written, payload);
+        assert!(run_command("exit 3", &payload, COMMAND_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn test_run_command_is_killed_after_timeout() {
+        let payload = payload(Event::ExitedOk, &make_entry(), None);
+        let started = Instant::now();
+
+        let result = run_command("exec sleep 30", &payload, Duration::from_millis(200));
+        assert!(matches!(result, Err(Error::Notify(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
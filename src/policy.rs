@@ -0,0 +1,401 @@
+use crate::config;
+use crate::db;
+use crate::error::{Error, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What a policy rule does with the tool calls it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Allow,
+    Deny,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Allow => "allow",
+            Action::Deny => "deny",
+        }
+    }
+}
+
+/// Outcome of checking one tool call; recorded with the call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub action: Action,
+    pub reason: Option<String>,
+}
+
+/// A policy file, e.g.:
+///
+/// ```toml
+/// default = "allow"
+///
+/// [[rule]]
+/// action = "deny"
+/// tool = "Bash"
+/// input = { command = 'rm\s+-[a-zA-Z]*r[a-zA-Z]*f' }
+/// reason = "rm -rf is not allowed"
+///
+/// [[rule]]
+/// action = "deny"
+/// tool = "Write|Edit|MultiEdit"
+/// outside_worktree = "file_path"
+/// reason = "Only files inside the worktree may be modified"
+/// ```
+///
+/// Rules are checked in order and the first match decides; calls no rule
+/// matches get `default`.
+#[derive(Debug)]
+pub struct Policy {
+    default: Action,
+    rules: Vec<Rule>,
+}
+
+/// A rule matches when all of its conditions hold; a rule without
+/// conditions matches every call.
+#[derive(Debug)]
+struct Rule {
+    action: Action,
+    /// Tool name, matched against the whole name
+    tool: Option<Regex>,
+    /// `tool_input` fields, each searched anywhere in the field's value
+    input: Vec<(String, Regex)>,
+    /// `tool_input` field holding a path that must resolve outside the worktree
+    outside_worktree: Option<String>,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PolicyFile {
+    default: Option<Action>,
+    rule: Vec<RuleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    action: Action,
+    tool: Option<String>,
+    #[serde(default)]
+    input: BTreeMap<String, String>,
+    outside_worktree: Option<String>,
+    reason: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Loading
+// ---------------------------------------------------------------------------
+
+fn default_path() -> Result<PathBuf> {
+    Ok(db::wortex_dir()?.join("policy.toml"))
+}
+
+/// Load the policy that applies to the worktree at `dir`: `policy.file`,
+/// else `~/.wortex/policy.toml`. Only the default file may be absent, which
+/// means no policy; a configured file that cannot be read is an error.
+pub fn load(dir: &Path) -> Result<Option<Policy>> {
+    let path = match config::load(dir)?.policy_file() {
+        Some(path) => path,
+        None => {
+            let path = default_path()?;
+            if !path.exists() {
+                return Ok(None);
+            }
+            path
+        }
+    };
+    let in_file = |message: String| Error::Policy(format!("{}: {}", path.display(), message));
+    let content = std::fs::read_to_string(&path).map_err(|e| in_file(e.to_string()))?;
+    parse(&content).map(Some).map_err(|e| match e {
+        Error::Policy(message) => in_file(message),
+        other => other,
+    })
+}
+
+pub fn parse(content: &str) -> Result<Policy> {
+    let file: PolicyFile =
+        toml::from_str(content).map_err(|e: toml::de::Error| Error::Policy(e.to_string()))?;
+
+    let rules = file
+        .rule
+        .into_iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            let compile = |pattern: &str| {
+                Regex::new(pattern)
+                    .map_err(|e| Error::Policy(format!("rule {}: {}", i + 1, e)))
+            };
+            Ok(Rule {
+                action: rule.action,
+                tool: rule
+                    .tool
+                    .map(|tool| compile(&format!("^(?:{})$", tool)))
+                    .transpose()?,
+                input: rule
+                    .input
+                    .iter()
+                    .map(|(field, pattern)| Ok((field.clone(), compile(pattern)?)))
+                    .collect::<Result<_>>()?,
+                outside_worktree: rule.outside_worktree,
+                reason: rule.reason,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Policy {
+        default: file.default.unwrap_or(Action::Allow),
+        rules,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+/// Check a call of the session whose worktree is `worktree`. `None` when no
+/// policy is configured. A policy that cannot be loaded denies everything,
+/// so a typo does not silently switch enforcement off.
+pub fn decide(worktree: &Path, tool_name: &str, input: &Value) -> Option<Decision> {
+    match load(worktree) {
+        Ok(policy) => policy.map(|p| p.evaluate(tool_name, input, worktree)),
+        Err(e) => Some(Decision {
+            action: Action::Deny,
+            reason: Some(e.to_string()),
+        }),
+    }
+}
+
+impl Policy {
+    pub fn evaluate(&self, tool_name: &str, input: &Value, worktree: &Path) -> Decision {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.matches(tool_name, input, worktree) {
+                let reason = rule.reason.clone().or_else(|| {
+                    (rule.action == Action::Deny)
+                        .then(|| format!("denied by wortex policy rule {}", i + 1))
+                });
+                return Decision {
+                    action: rule.action,
+                    reason,
+                };
+            }
+        }
+        Decision {
+            action: self.default,
+            reason: (self.default == Action::Deny)
+                .then(|| format!("no wortex policy rule allows {}", tool_name)),
+        }
+    }
+}
+
+impl Rule {
+    fn matches(&self, tool_name: &str, input: &Value, worktree: &Path) -> bool {
+        if let Some(ref tool) = self.tool
+            && !tool.is_match(tool_name)
+        {
+            return false;
+        }
+
+        let all_fields_match = self.input.iter().all(|(field, pattern)| {
+            match input.get(field) {
+                None | Some(Value::Null) => false,
+                Some(Value::String(s)) => pattern.is_match(s),
+                Some(other) => pattern.is_match(&other.to_string()),
+            }
+        });
+        if !all_fields_match {
+            return false;
+        }
+
+        match self.outside_worktree {
+            Some(ref field) => input
+                .get(field)
+                .and_then(Value::as_str)
+                .is_some_and(|path| is_outside(worktree, path)),
+            None => true,
+        }
+    }
+}
+
+/// Whether `path` (relative paths are taken from `worktree`) leaves the
+/// worktree. Resolved lexically: the file may not exist yet.
+fn is_outside(worktree: &Path, path: &str) -> bool {
+    let path = config::expand_tilde(path);
+    !normalize(&worktree.join(path)).starts_with(normalize(worktree))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::fixture::run_git;
+    use serde_json::json;
+
+    const POLICY: &str = r#"
+        [[rule]]
+        action = "allow"
+        tool = "Bash"
+        input = { command = '^git push --force-with-lease' }
+
+        [[rule]]
+        action = "deny"
+        tool = "Bash"
+        input = { command = '\bgit\s+push\b.*(--force|\s-f\b)' }
+        reason = "Force pushes are not allowed"
+
+        [[rule]]
+        action = "deny"
+        tool = "Bash"
+        input = { command = '\brm\s+-[a-zA-Z]*r[a-zA-Z]*f' }
+
+        [[rule]]
+        action = "deny"
+        tool = "Write|Edit|MultiEdit"
+        outside_worktree = "file_path"
+        reason = "Only files inside the worktree may be modified"
+    "#;
+
+    fn evaluate(policy: &Policy, tool_name: &str, input: Value) -> Decision {
+        policy.evaluate(tool_name, &input, Path::new("/work/proj-feature"))
+    }
+
+    #[test]
+    fn test_first_matching_rule_decides() {
+        let policy = parse(POLICY).unwrap();
+
+        let decision = evaluate(&policy, "Bash", json!({"command": "git push --force origin x"}));
+        assert_eq!(decision.action, Action::Deny);
+        assert_eq!(decision.reason.as_deref(), Some("Force pushes are not allowed"));
+
+        let decision = evaluate(&policy, "Bash", json!({"command": "git push --force-with-lease"}));
+        assert_eq!(decision, Decision { action: Action::Allow, reason: None });
+
+        let decision = evaluate(&policy, "Bash", json!({"command": "cd /tmp && rm -rf build"}));
+        assert_eq!(decision.action, Action::Deny);
+        assert_eq!(decision.reason.as_deref(), Some("denied by wortex policy rule 3"));
+
+        let decision = evaluate(&policy, "Bash", json!({"command": "git push origin x"}));
+        assert_eq!(decision.action, Action::Allow);
+    }
+
+    #[test]
+    fn test_tool_name_matches_whole_name() {
+        let policy = parse(POLICY).unwrap();
+        // "BashOutput" is not "Bash"
+        let decision = evaluate(&policy, "BashOutput", json!({"command": "rm -rf /"}));
+        assert_eq!(decision.action, Action::Allow);
+    }
+
+    #[test]
+    fn test_outside_worktree() {
+        let policy = parse(POLICY).unwrap();
+
+        for path in ["/work/proj-feature/src/main.rs", "src/main.rs", "/work/proj-feature/a/../b"] {
+            let decision = evaluate(&policy, "Write", json!({"file_path": path}));
+            assert_eq!(decision.action, Action::Allow, "{}", path);
+        }
+        for path in ["/etc/passwd", "../proj/src/main.rs", "/work/proj-feature-other/x", "/work/proj-feature/../x"] {
+            let decision = evaluate(&policy, "Edit", json!({"file_path": path}));
+            assert_eq!(decision.action, Action::Deny, "{}", path);
+        }
+
+        // Reads are not covered by the rule, and a missing field never matches
+        assert_eq!(evaluate(&policy, "Read", json!({"file_path": "/etc/passwd"})).action, Action::Allow);
+        assert_eq!(evaluate(&policy, "Write", json!({})).action, Action::Allow);
+    }
+
+    #[test]
+    fn test_default_deny() {
+        let policy = parse(
+            r#"
+            default = "deny"
+
+            [[rule]]
+            action = "allow"
+            tool = "Read|Grep|Glob"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&policy, "Grep", json!({"pattern": "x"})).action, Action::Allow);
+        let decision = evaluate(&policy, "Bash", json!({"command": "ls"}));
+        assert_eq!(decision.action, Action::Deny);
+        assert_eq!(decision.reason.as_deref(), Some("no wortex policy rule allows Bash"));
+    }
+
+    #[test]
+    fn test_non_string_input_fields() {
+        let policy = parse(
+            r#"
+            [[rule]]
+            action = "deny"
+            input = { run_in_background = "true" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&policy, "Bash", json!({"run_in_background": true})).action, Action::Deny);
+        assert_eq!(evaluate(&policy, "Bash", json!({"run_in_background": false})).action, Action::Allow);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(parse(r#"default = "maybe""#), Err(Error::Policy(_))));
+        assert!(matches!(
+            parse("[[rule]]\naction = \"deny\"\ncomand = \"x\""),
+            Err(Error::Policy(_))
+        ));
+        match parse("[[rule]]\naction = \"deny\"\ntool = \"(\"") {
+            Err(Error::Policy(message)) => assert!(message.starts_with("rule 1:"), "{}", message),
+            other => panic!("expected a policy error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unreadable_or_missing_policy_denies() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let policy_file = temp_dir.path().join("policy.toml");
+        std::fs::write(&policy_file, "[[rule]]\naction = \"nope\"\n").unwrap();
+        std::fs::write(
+            temp_dir.path().join(config::REPO_CONFIG_FILE),
+            format!("[policy]\nfile = {:?}\n", policy_file.display().to_string()),
+        )
+        .unwrap();
+        run_git(temp_dir.path(), &["init", "-q"]);
+
+        let decision = decide(temp_dir.path(), "Read", &json!({})).unwrap();
+        assert_eq!(decision.action, Action::Deny);
+        let reason = decision.reason.unwrap();
+        assert!(reason.starts_with(&format!("Policy error: {}: ", policy_file.display())), "{}", reason);
+
+        // A configured file that is missing denies too
+        std::fs::remove_file(&policy_file).unwrap();
+        let decision = decide(temp_dir.path(), "Read", &json!({})).unwrap();
+        assert_eq!(decision.action, Action::Deny);
+        let reason = decision.reason.unwrap();
+        assert!(reason.starts_with(&format!("Policy error: {}: ", policy_file.display())), "{}", reason);
+    }
+}
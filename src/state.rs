@@ -1,8 +1,9 @@
 use crate::db;
+use crate::git;
 use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 // ---------------------------------------------------------------------------
@@ -20,7 +21,96 @@ pub struct Entry {
     pub command: Command,
     pub exit_kill: Option<ExitKill>,
     pub exit_code: Option<i32>,
+    pub status: ProcessStatus,
+    pub pid: Option<u32>,
+    /// Notification the agent is waiting on while `status` is `waiting`
+    pub blocked_on: Option<String>,
+    /// Main working tree of the repo the worktree belongs to
+    pub repo_path: Option<PathBuf>,
+    /// Shared `.git` directory of that repo
+    pub git_common_dir: Option<PathBuf>,
+    /// Git remote the session was created against
+    pub remote: Option<String>,
+    /// Branch on `remote` the session was started from
+    pub base: Option<String>,
+    /// Whether wortex created `branch`; `kill` only deletes branches it created
+    pub created_branch: bool,
+    /// Set once the entry is killed or cleaned up; kept so its logs stay readable
+    pub removed_at: Option<DateTime<Utc>>,
+    /// How many times `__run` has started the command (see `wortex restart`)
+    pub run_count: u32,
+    /// Commit the remote branch pointed at after the last `wortex push`
+    pub pushed_sha: Option<String>,
+    pub pushed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Entry {
+    /// Directory git commands for this entry run in. Entries recorded before
+    /// the repo was stored fall back to the repo owning the worktree. Fails
+    /// when none of them exists rather than guessing: git commands run in the
+    /// wrong repo could remove or push the wrong branch.
+    pub fn repo_dir(&self) -> Result<PathBuf> {
+        [&self.repo_path, &self.git_common_dir]
+            .into_iter()
+            .flatten()
+            .find(|p| p.exists())
+            .cloned()
+            .or_else(|| git::main_repo_root(&self.path).ok())
+            .ok_or_else(|| Error::RepoNotFound(format!("{}:{}", self.project, self.branch)))
+    }
+
+    /// Identity of the entry's repo: its shared git directory, or the project
+    /// prefix for entries recorded before that was stored. Prefixes are not
+    /// unique (`my-project` and `mega-proxy` are both "mp").
+    pub fn repo_key(&self) -> String {
+        match self.git_common_dir {
+            Some(ref dir) => dir.to_string_lossy().to_string(),
+            None => self.project.clone(),
+        }
+    }
+
+    /// A spawned `true` command in `/tmp/<project>-<branch>` with no repo
+    /// recorded. Tests change what they need with struct update syntax.
+    #[cfg(test)]
+    pub fn for_test(project: &str, branch: &str) -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            project: project.to_string(),
+            branch: branch.to_string(),
+            path: PathBuf::from(format!("/tmp/{}-{}", project, branch)),
+            tmux_session: "dev".to_string(),
+            tmux_window: branch.to_string(),
+            command: Command::Raw {
+                cmd: "true".to_string(),
+            },
+            exit_kill: None,
+            exit_code: None,
+            status: ProcessStatus::Spawned,
+            pid: None,
+            blocked_on: None,
+            repo_path: None,
+            git_common_dir: None,
+            remote: None,
+            base: None,
+            created_branch: true,
+            removed_at: None,
+            run_count: 0,
+            pushed_sha: None,
+            pushed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Project prefix, plus the repo path when there is one: "mp (/src/my-project)".
+    fn repo_label(&self) -> String {
+        match self.repo_path.as_ref().or(self.git_common_dir.as_ref()) {
+            Some(path) => format!("{} ({})", self.project, path.display()),
+            None => self.project.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +123,8 @@ pub enum Command {
     Raw {
         cmd: String,
     },
+    /// Adopted worktree/window not started by wortex; there is nothing to run
+    External,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +143,61 @@ impl ExitKill {
     }
 }
 
+/// Lifecycle of the process supervised by `wortex __run`.
+///
+/// spawned -> running <-> waiting -> exited | killed | signaled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessStatus {
+    /// Entry created, `__run` has not started the child yet
+    Spawned,
+    /// Child process started, pid recorded
+    Running,
+    /// Agent is blocked on the user (permission prompt, idle input); cleared
+    /// by its next tool call
+    Waiting,
+    /// Child exited normally with an exit code
+    Exited,
+    /// Child was terminated by SIGKILL
+    Killed,
+    /// Child was terminated by any other signal
+    Signaled,
+    /// Adopted entry; its process is not supervised by `__run`
+    External,
+}
+
+impl ProcessStatus {
+    /// Whether the supervised command is still alive (running or waiting).
+    pub fn is_live(&self) -> bool {
+        matches!(self, ProcessStatus::Running | ProcessStatus::Waiting)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessStatus::Spawned => "spawned",
+            ProcessStatus::Running => "running",
+            ProcessStatus::Waiting => "waiting",
+            ProcessStatus::Exited => "exited",
+            ProcessStatus::Killed => "killed",
+            ProcessStatus::Signaled => "signaled",
+            ProcessStatus::External => "external",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spawned" => Some(ProcessStatus::Spawned),
+            "running" => Some(ProcessStatus::Running),
+            "waiting" => Some(ProcessStatus::Waiting),
+            "exited" => Some(ProcessStatus::Exited),
+            "killed" => Some(ProcessStatus::Killed),
+            "signaled" => Some(ProcessStatus::Signaled),
+            "external" => Some(ProcessStatus::External),
+            _ => None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Kept for backward compat -- State wrapper used by list/cleanup/status
 // ---------------------------------------------------------------------------
@@ -90,6 +237,20 @@ pub fn initialize() -> Result<()> {
     Ok(())
 }
 
+/// Check whether a process with the given PID exists (`kill -0`).
+pub fn pid_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Pane output of an entry, captured with `tmux pipe-pane`.
+pub fn log_path(id: Uuid) -> Result<PathBuf> {
+    Ok(db::wortex_dir()?.join("logs").join(format!("{}.log", id)))
+}
+
 // ---------------------------------------------------------------------------
 // Public API -- backed by SQLite via db module
 // ---------------------------------------------------------------------------
@@ -108,14 +269,53 @@ pub fn add_entry(entry: Entry) -> Result<()> {
     db::insert_process(&conn, &entry)
 }
 
-pub fn remove_entry(id: Uuid) -> Result<()> {
+/// Hide the entry from listings; its row and log file are kept.
+pub fn archive_entry(id: Uuid) -> Result<()> {
     let conn = db::open_and_init()?;
-    db::delete_process(&conn, id)
+    db::archive_process(&conn, id)
 }
 
-pub fn update_exit_code(id: Uuid, code: i32) -> Result<()> {
+/// Delete the entry, its tool calls and its log file for good.
+pub fn purge_entry(id: Uuid) -> Result<()> {
     let conn = db::open_and_init()?;
-    db::set_exit_code(&conn, id, code)
+    db::delete_process(&conn, id)?;
+    match std::fs::remove_file(log_path(id)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+pub fn load_archived() -> Result<Vec<Entry>> {
+    let conn = db::open_and_init()?;
+    db::get_archived_processes(&conn)
+}
+
+pub fn mark_running(id: Uuid, pid: u32) -> Result<()> {
+    let conn = db::open_and_init()?;
+    db::set_running(&conn, id, pid)
+}
+
+pub fn reset_for_restart(id: Uuid) -> Result<()> {
+    let conn = db::open_and_init()?;
+    db::reset_process(&conn, id)
+}
+
+pub fn mark_terminated(id: Uuid, status: ProcessStatus, code: i32) -> Result<()> {
+    let conn = db::open_and_init()?;
+    db::set_terminated(&conn, id, status, code)
+}
+
+pub fn mark_pushed(id: Uuid, remote: &str, sha: &str) -> Result<()> {
+    let conn = db::open_and_init()?;
+    db::set_pushed(&conn, id, remote, sha)
+}
+
+/// Append a row to the entry's `events` table (see `db::insert_event`);
+/// `kind` is e.g. `send::EVENT_INPUT`.
+pub fn record_event(id: Uuid, kind: &str, detail: Option<&str>) -> Result<()> {
+    let conn = db::open_and_init()?;
+    db::insert_event(&conn, id, kind, detail)?;
+    Ok(())
 }
 
 pub fn find_by_id(id: Uuid) -> Result<Option<Entry>> {
@@ -123,7 +323,263 @@ pub fn find_by_id(id: Uuid) -> Result<Option<Entry>> {
     db::get_process_by_id(&conn, id)
 }
 
-pub fn find_by_branch(branch: &str) -> Result<Option<Entry>> {
+/// The live entry for `branch` in the repo with this shared git directory
+/// (or, for entries recorded before it was stored, with this project prefix).
+pub fn find_in_repo(git_common_dir: &Path, project: &str, branch: &str) -> Result<Option<Entry>> {
+    let conn = db::open_and_init()?;
+    db::get_process_in_repo(&conn, git_common_dir, project, branch)
+}
+
+// ---------------------------------------------------------------------------
+// Resolving branch arguments
+// ---------------------------------------------------------------------------
+
+/// Which repo a branch argument is looked up in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectScope {
+    /// Named with `--project` or `project:branch`; must match a single repo
+    Explicit(String),
+    /// The repo containing the current directory
+    Current {
+        project: String,
+        git_common_dir: PathBuf,
+    },
+    /// Outside any repo: the branch must be unique across repos
+    Any,
+}
+
+/// Split `project:branch`. Git refuses ':' in branch names, so the first
+/// colon always separates the project.
+pub fn split_target(target: &str) -> (Option<&str>, &str) {
+    match target.split_once(':') {
+        Some((project, branch)) if !project.is_empty() => (Some(project), branch),
+        _ => (None, target),
+    }
+}
+
+/// Find the live entry a branch argument refers to.
+pub fn resolve(target: &str, scope: &ProjectScope) -> Result<Entry> {
+    resolve_in(target, scope, false)
+}
+
+/// Like `resolve`, but falls back to the most recently removed entry.
+pub fn resolve_including_removed(target: &str, scope: &ProjectScope) -> Result<Entry> {
+    resolve_in(target, scope, true)
+}
+
+fn resolve_in(target: &str, scope: &ProjectScope, include_removed: bool) -> Result<Entry> {
+    let (project, branch) = split_target(target);
+    let scope = match project {
+        Some(project) => ProjectScope::Explicit(project.to_string()),
+        None => scope.clone(),
+    };
     let conn = db::open_and_init()?;
-    db::get_process_by_branch(&conn, branch)
+    pick(
+        branch,
+        &scope,
+        db::get_processes_by_branch(&conn, branch, include_removed)?,
+    )
+}
+
+/// Choose among `candidates` (preferred ones first) for `scope`. Entries of
+/// different repos are told apart by `Entry::repo_key`, not by project prefix.
+fn pick(branch: &str, scope: &ProjectScope, candidates: Vec<Entry>) -> Result<Entry> {
+    match scope {
+        ProjectScope::Explicit(project) => {
+            let matching: Vec<Entry> = candidates
+                .into_iter()
+                .filter(|e| e.project == *project)
+                .collect();
+            let target = format!("{}:{}", project, branch);
+            single_repo(target.clone(), matching).ok_or(Error::EntryNotFound(target))?
+        }
+        ProjectScope::Current {
+            project,
+            git_common_dir,
+        } => {
+            let in_repo = |e: &&Entry| match e.git_common_dir {
+                Some(ref dir) => dir == git_common_dir,
+                None => e.project == *project,
+            };
+            if let Some(entry) = candidates.iter().find(in_repo) {
+                return Ok(entry.clone());
+            }
+            if candidates.is_empty() {
+                Err(Error::EntryNotFound(branch.to_string()))
+            } else {
+                Err(Error::EntryInOtherProject(
+                    format!("{}:{}", project, branch),
+                    distinct_repos(&candidates).join(", "),
+                ))
+            }
+        }
+        ProjectScope::Any => single_repo(branch.to_string(), candidates)
+            .ok_or_else(|| Error::EntryNotFound(branch.to_string()))?,
+    }
+}
+
+/// The first candidate when they all belong to one repo, an ambiguity error
+/// when they span several, `None` when there are none.
+fn single_repo(target: String, candidates: Vec<Entry>) -> Option<Result<Entry>> {
+    match distinct_repos(&candidates).as_slice() {
+        [] => None,
+        [_] => candidates.into_iter().next().map(Ok),
+        labels => Some(Err(Error::AmbiguousEntry(target, labels.join(", ")))),
+    }
+}
+
+/// One label per repo among `entries`, in order of first appearance.
+fn distinct_repos(entries: &[Entry]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut labels = Vec::new();
+    for entry in entries {
+        let key = entry.repo_key();
+        if !keys.contains(&key) {
+            keys.push(key);
+            labels.push(entry.repo_label());
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_dir_prefers_existing_recorded_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut entry = Entry::for_test("mp", "fix-ci");
+        entry.repo_path = Some(PathBuf::from("/nonexistent/repo"));
+        entry.git_common_dir = Some(dir.path().to_path_buf());
+        assert_eq!(entry.repo_dir().unwrap(), dir.path());
+    }
+
+    #[test]
+    fn test_repo_dir_refuses_to_guess() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut entry = Entry::for_test("mp", "fix-ci");
+        entry.repo_path = Some(dir.path().join("missing"));
+        entry.git_common_dir = None;
+        entry.path = dir.path().join("mp-fix-ci");
+
+        match entry.repo_dir() {
+            Err(Error::RepoNotFound(name)) => assert_eq!(name, "mp:fix-ci"),
+            other => panic!("expected RepoNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_split_target() {
+        assert_eq!(split_target("fix-ci"), (None, "fix-ci"));
+        assert_eq!(split_target("mp:fix-ci"), (Some("mp"), "fix-ci"));
+        assert_eq!(split_target(":fix-ci"), (None, ":fix-ci"));
+    }
+
+    fn in_repo(mut entry: Entry, repo: &str) -> Entry {
+        entry.repo_path = Some(PathBuf::from(repo));
+        entry.git_common_dir = Some(PathBuf::from(repo).join(".git"));
+        entry
+    }
+
+    fn current(project: &str, repo: &str) -> ProjectScope {
+        ProjectScope::Current {
+            project: project.to_string(),
+            git_common_dir: PathBuf::from(repo).join(".git"),
+        }
+    }
+
+    #[test]
+    fn test_pick_prefers_current_project() {
+        let a = in_repo(Entry::for_test("mp", "fix-ci"), "/src/my-project");
+        let b = in_repo(Entry::for_test("ot", "fix-ci"), "/src/other");
+        let scope = current("ot", "/src/other");
+
+        let found = pick("fix-ci", &scope, vec![a, b.clone()]).unwrap();
+        assert_eq!(found.id, b.id);
+    }
+
+    #[test]
+    fn test_pick_current_does_not_fall_through_to_other_project() {
+        let scope = current("ot", "/src/other");
+        let err = pick("fix-ci", &scope, vec![Entry::for_test("mp", "fix-ci")]).unwrap_err();
+        assert!(matches!(err, Error::EntryInOtherProject(..)));
+    }
+
+    #[test]
+    fn test_pick_current_tells_repos_with_the_same_prefix_apart() {
+        // my-project and mega-proxy both derive "mp"
+        let a = in_repo(Entry::for_test("mp", "fix-ci"), "/src/my-project");
+        let b = in_repo(Entry::for_test("mp", "fix-ci"), "/src/mega-proxy");
+
+        let found = pick("fix-ci", &current("mp", "/src/mega-proxy"), vec![a.clone(), b.clone()]);
+        assert_eq!(found.unwrap().id, b.id);
+        let found = pick("fix-ci", &current("mp", "/src/my-project"), vec![a.clone(), b.clone()]);
+        assert_eq!(found.unwrap().id, a.id);
+
+        // A fork elsewhere with the same name is another repo
+        let err = pick("fix-ci", &current("mp", "/forks/my-project"), vec![a, b]).unwrap_err();
+        assert!(matches!(err, Error::EntryInOtherProject(..)));
+    }
+
+    #[test]
+    fn test_pick_current_matches_legacy_entries_by_project() {
+        let legacy = Entry::for_test("mp", "fix-ci");
+        let found = pick("fix-ci", &current("mp", "/src/my-project"), vec![legacy.clone()]);
+        assert_eq!(found.unwrap().id, legacy.id);
+    }
+
+    #[test]
+    fn test_pick_explicit_prefix_shared_by_two_repos_is_ambiguous() {
+        let a = in_repo(Entry::for_test("mp", "fix-ci"), "/src/my-project");
+        let b = in_repo(Entry::for_test("mp", "fix-ci"), "/src/mega-proxy");
+        let scope = ProjectScope::Explicit("mp".to_string());
+
+        match pick("fix-ci", &scope, vec![a, b]) {
+            Err(Error::AmbiguousEntry(target, repos)) => {
+                assert_eq!(target, "mp:fix-ci");
+                assert_eq!(repos, "mp (/src/my-project), mp (/src/mega-proxy)");
+            }
+            other => panic!("expected an ambiguity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pick_explicit() {
+        let a = Entry::for_test("mp", "fix-ci");
+        let scope = ProjectScope::Explicit("mp".to_string());
+        assert_eq!(pick("fix-ci", &scope, vec![a.clone()]).unwrap().id, a.id);
+
+        let scope = ProjectScope::Explicit("ot".to_string());
+        assert!(matches!(
+            pick("fix-ci", &scope, vec![a]),
+            Err(Error::EntryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_pick_any_same_project_history() {
+        // A live entry and an archived one for the same project are not ambiguous
+        let live = Entry::for_test("mp", "fix-ci");
+        let mut archived = Entry::for_test("mp", "fix-ci");
+        archived.removed_at = Some(Utc::now());
+
+        let found = pick("fix-ci", &ProjectScope::Any, vec![live.clone(), archived]).unwrap();
+        assert_eq!(found.id, live.id);
+    }
+
+    #[test]
+    fn test_pick_any() {
+        let a = Entry::for_test("mp", "fix-ci");
+        let b = Entry::for_test("ot", "fix-ci");
+        assert_eq!(pick("fix-ci", &ProjectScope::Any, vec![a.clone()]).unwrap().id, a.id);
+        assert!(matches!(
+            pick("fix-ci", &ProjectScope::Any, vec![a, b]),
+            Err(Error::AmbiguousEntry(..))
+        ));
+        assert!(matches!(
+            pick("fix-ci", &ProjectScope::Any, vec![]),
+            Err(Error::EntryNotFound(_))
+        ));
+    }
 }
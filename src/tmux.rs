@@ -1,7 +1,9 @@
 use crate::error::{Error, Result};
 use std::env;
-use std::path::Path;
-use std::process::Command;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 pub fn is_inside_tmux() -> bool {
     env::var("TMUX").is_ok()
@@ -19,13 +21,18 @@ pub fn get_current_session() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
+/// Runs in a new window until the real command replaces it; prints nothing.
+const PLACEHOLDER_COMMAND: &str = "tail -f /dev/null";
+
+/// Create a window running `command` with its output appended to `log_path`.
+/// See `respawn_logged`.
 pub fn create_window(
     session: &str,
     window_name: &str,
     working_dir: &Path,
     command: &str,
+    log_path: &Path,
 ) -> Result<()> {
-    // Create the window with the command
     // Append colon to session name to avoid ambiguity with numeric window indices
     let session_target = format!("{}:", session);
     let output = Command::new("tmux")
@@ -37,7 +44,7 @@ pub fn create_window(
             window_name,
             "-c",
             working_dir.to_str().unwrap(),
-            command,
+            PLACEHOLDER_COMMAND,
         ])
         .output()?;
 
@@ -65,9 +72,190 @@ pub fn create_window(
         )));
     }
 
+    pipe_pane(session, window_name, log_path)?;
+    respawn_pane(session, window_name, working_dir, command)
+}
+
+/// Replace the window's pane with `command`, appending its output to
+/// `log_path` from the first byte.
+///
+/// A pipe cannot be attached to a dead pane, and attaching it after the
+/// command started would miss its first output. So the pane is respawned
+/// with a silent placeholder, the log attached, and the pane respawned again
+/// with the command; the pipe survives respawns.
+pub fn respawn_logged(
+    session: &str,
+    window: &str,
+    working_dir: &Path,
+    command: &str,
+    log_path: &Path,
+) -> Result<()> {
+    respawn_pane(session, window, working_dir, PLACEHOLDER_COMMAND)?;
+    pipe_pane(session, window, log_path)?;
+    respawn_pane(session, window, working_dir, command)
+}
+
+/// Append everything the window's pane prints to `log_path`, so it survives
+/// the window. Replaces any pipe already attached to the pane.
+pub fn pipe_pane(session: &str, window: &str, log_path: &Path) -> Result<()> {
+    let output = Command::new("tmux")
+        .args([
+            "pipe-pane",
+            "-t",
+            &format!("{}:{}", session, window),
+            &format!("cat >> {}", shell_quote(&log_path.to_string_lossy())),
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Tmux(format!("Failed to pipe pane: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Replace whatever runs in the window's pane with `command` (killing it if it
+/// is still alive), keeping the window and its layout.
+pub fn respawn_pane(session: &str, window: &str, working_dir: &Path, command: &str) -> Result<()> {
+    let output = Command::new("tmux")
+        .args([
+            "respawn-pane",
+            "-k",
+            "-t",
+            &format!("{}:{}", session, window),
+            "-c",
+            working_dir.to_str().unwrap(),
+            command,
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Tmux(format!("Failed to respawn pane: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Whether the window's pane has exited (kept open by remain-on-exit).
+pub fn pane_dead(session: &str, window: &str) -> Result<bool> {
+    let output = Command::new("tmux")
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            &format!("{}:{}", session, window),
+            "#{pane_dead}",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Tmux(format!("Failed to query pane: {}", stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+/// Type `text` into the window's pane. Multi-line text is pasted with
+/// bracketed paste so the program receives it as one input instead of one
+/// submission per line. With `enter`, Enter is pressed afterwards.
+pub fn send_text(session: &str, window: &str, text: &str, enter: bool) -> Result<()> {
+    let target = format!("{}:{}", session, window);
+
+    if text.contains('\n') {
+        let buffer = format!("wortex-send-{}", std::process::id());
+        let mut child = Command::new("tmux")
+            .args(["load-buffer", "-b", &buffer, "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(text.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Tmux(format!("Failed to load buffer: {}", stderr)));
+        }
+
+        // -p: bracketed paste, -d: delete the buffer afterwards
+        let output = Command::new("tmux")
+            .args(["paste-buffer", "-p", "-d", "-b", &buffer, "-t", &target])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Tmux(format!("Failed to paste buffer: {}", stderr)));
+        }
+    } else if !text.is_empty() {
+        // -l: send the text literally, not as key names
+        let output = Command::new("tmux")
+            .args(["send-keys", "-t", &target, "-l", text])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Tmux(format!("Failed to send keys: {}", stderr)));
+        }
+    }
+
+    if enter {
+        let output = Command::new("tmux")
+            .args(["send-keys", "-t", &target, "Enter"])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Tmux(format!("Failed to send keys: {}", stderr)));
+        }
+    }
+
     Ok(())
 }
 
+/// Show `text` in the status line of clients attached to the session.
+pub fn display_message(session: &str, window: &str, text: &str) -> Result<()> {
+    let output = Command::new("tmux")
+        .args(["display-message", "-t", &format!("{}:{}", session, window), text])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Tmux(format!("Failed to display message: {}", stderr)));
+    }
+
+    Ok(())
+}
+
+/// Ring the bell in the window's pane by writing BEL to its tty, which sets
+/// the window's bell (and activity) flag in the status line.
+pub fn ring_bell(session: &str, window: &str) -> Result<()> {
+    let output = Command::new("tmux")
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            &format!("{}:{}", session, window),
+            "#{pane_tty}",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Tmux(format!("Failed to query pane: {}", stderr)));
+    }
+
+    let tty = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    OpenOptions::new().write(true).open(&tty)?.write_all(b"\x07")?;
+    Ok(())
+}
+
+/// Single-quote `s` for the shell tmux runs pipe-pane commands in.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 pub fn window_exists(session: &str, window: &str) -> Result<bool> {
     let output = Command::new("tmux")
         .args(["list-windows", "-t", session, "-F", "#W"])
@@ -82,6 +270,43 @@ pub fn window_exists(session: &str, window: &str) -> Result<bool> {
     Ok(windows.lines().any(|w| w == window))
 }
 
+/// A tmux window and the working directory of its active pane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub session: String,
+    pub window: String,
+    pub pane_path: PathBuf,
+}
+
+/// Every window across all sessions of the current server.
+pub fn list_all_windows() -> Result<Vec<WindowInfo>> {
+    let output = Command::new("tmux")
+        .args([
+            "list-windows",
+            "-a",
+            "-F",
+            "#{session_name}\t#{window_name}\t#{pane_current_path}",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        // No server running
+        return Ok(Vec::new());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            Some(WindowInfo {
+                session: parts.next()?.to_string(),
+                window: parts.next()?.to_string(),
+                pane_path: PathBuf::from(parts.next()?),
+            })
+        })
+        .collect())
+}
+
 pub fn kill_window(session: &str, window: &str) -> Result<()> {
     let output = Command::new("tmux")
         .args(["kill-window", "-t", &format!("{}:{}", session, window)])
@@ -107,3 +332,14 @@ pub fn select_window(session: &str, window: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/home/u/.wortex/logs/a.log"), "'/home/u/.wortex/logs/a.log'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}